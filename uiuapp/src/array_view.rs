@@ -0,0 +1,118 @@
+use dioxus::prelude::*;
+use uiua::Value;
+
+/// How many rows (or elements, for lists) of an axis are shown before it's collapsed
+pub const COLLAPSE_AFTER: usize = 8;
+
+/// The css class of a scalar, based on its type
+fn css_of_value(value: &Value) -> &'static str {
+    match value {
+        Value::Num(_) | Value::Byte(_) => "array-number",
+        Value::Complex(_) => "array-complex",
+        Value::Char(_) => "array-char",
+        Value::Box(_) => "array-box",
+    }
+}
+
+/// Draws a value as nested boxes, with a separator between each rank, the way the pad does
+#[component]
+pub fn ArrayView(value: Value) -> Element {
+    let mut expanded = use_signal(|| false);
+    let limit = if expanded() {
+        usize::MAX
+    } else {
+        COLLAPSE_AFTER
+    };
+
+    // Things we can't lay out better than uiua itself: empty arrays, maps and strings
+    let is_empty = value.shape.contains(&0);
+    let is_string = matches!(value, Value::Char(_)) && value.rank() == 1;
+    if is_empty || is_string || value.meta().map_keys.is_some() {
+        return rsx! {
+            span { class: css_of_value(&value), "{value.show()}" }
+        };
+    }
+
+    let hidden = value.row_count().saturating_sub(limit);
+    // Matrices collapse their columns too, and either can be expanded
+    let hidden_columns = match value.rank() {
+        2 => value.shape[1].saturating_sub(limit),
+        _ => 0,
+    };
+    let hidden_label = match (hidden, hidden_columns) {
+        (rows, 0) => format!("… {rows} more"),
+        (0, columns) => format!("… {columns} more columns"),
+        (rows, columns) => format!("… {rows} more rows, {columns} more columns"),
+    };
+    let collapse_button = rsx! {
+        if hidden > 0 || hidden_columns > 0 {
+            button { class: "array-expand",
+                     onclick: move |e| {
+                         e.stop_propagation();
+                         expanded.set(true);
+                     },
+                     "{hidden_label}"
+            }
+        } else if expanded() {
+            button { class: "array-expand",
+                     onclick: move |e| {
+                         e.stop_propagation();
+                         expanded.set(false);
+                     },
+                     "collapse"
+            }
+        }
+    };
+
+    match value.rank() {
+        0 => match value {
+            Value::Box(b) => {
+                let inner = b.as_scalar().map(|b| b.0.clone()).unwrap_or_default();
+                rsx! {
+                    div { class: "array array-box",
+                          ArrayView { value: inner }
+                    }
+                }
+            }
+            _ => rsx! {
+                span { class: css_of_value(&value), "{value.show()}" }
+            },
+        },
+        1 => rsx! {
+            div { class: "array array-rank-1",
+                  for row in value.rows().take(limit) {
+                      ArrayView { value: row }
+                  }
+                  {collapse_button}
+            }
+        },
+        2 => {
+            let columns = value.shape[1] - hidden_columns;
+            rsx! {
+                div { class: "array array-rank-2",
+                      style: "grid-template-columns: repeat({columns + (hidden_columns > 0) as usize}, auto);",
+                      for row in value.rows().take(limit) {
+                          for cell in row.rows().take(columns) {
+                              ArrayView { value: cell }
+                          }
+                          if hidden_columns > 0 {
+                              span { class: "array-ellipsis", "…" }
+                          }
+                      }
+                }
+                {collapse_button}
+            }
+        }
+        rank => rsx! {
+            div { class: "array array-rank-{rank.min(4)}",
+                  for (i, row) in value.rows().take(limit).enumerate() {
+                      if i > 0 {
+                          div { class: "rank-separator" }
+                      }
+                      ArrayView { value: row }
+                  }
+                  {collapse_button}
+            }
+        },
+    }
+}
@@ -0,0 +1,211 @@
+//! Named scratch buffers, each with its own input and scrollback
+use crate::{storage, RunRecord, Settings};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const BUFFERS_KEY: &str = "uiuapp-buffers";
+/// Outputs with more elements than this aren't persisted (audio alone can be hundreds of KB)
+const MAX_PERSISTED_ELEMENTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScratchBuffer {
+    pub id: u64,
+    pub name: String,
+    /// Whatever was left in the input when the buffer was last used
+    pub input: String,
+    /// Library buffers are loaded before any code run in another buffer
+    pub is_library: bool,
+    /// Isolated buffers don't see any library buffers
+    pub isolated: bool,
+}
+
+impl ScratchBuffer {
+    fn new(id: u64, name: String) -> Self {
+        Self {
+            id,
+            name,
+            input: String::new(),
+            is_library: false,
+            isolated: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScratchBuffers {
+    pub buffers: Vec<ScratchBuffer>,
+    /// The id of the buffer being edited
+    pub active: u64,
+}
+
+impl Default for ScratchBuffers {
+    fn default() -> Self {
+        Self {
+            buffers: vec![ScratchBuffer::new(0, "main".to_string())],
+            active: 0,
+        }
+    }
+}
+
+impl ScratchBuffers {
+    pub fn load() -> Self {
+        storage::load::<Self>(BUFFERS_KEY)
+            .filter(|b| b.buffers.iter().any(|buf| buf.id == b.active))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(BUFFERS_KEY, self);
+    }
+
+    pub fn active(&self) -> &ScratchBuffer {
+        self.buffers
+            .iter()
+            .find(|b| b.id == self.active)
+            .expect("the active buffer always exists")
+    }
+
+    pub fn active_mut(&mut self) -> &mut ScratchBuffer {
+        let active = self.active;
+        self.buffers
+            .iter_mut()
+            .find(|b| b.id == active)
+            .expect("the active buffer always exists")
+    }
+
+    /// Adds an empty buffer and returns its id, without switching to it
+    pub fn add(&mut self) -> u64 {
+        let id = self.buffers.iter().map(|b| b.id + 1).max().unwrap_or(0);
+        self.buffers
+            .push(ScratchBuffer::new(id, format!("scratch {id}")));
+        id
+    }
+
+    /// Like [ScratchBuffers::load], but code shared through the url opens in a buffer of its own
+    /// (or in the buffer that already holds it)
+    pub fn load_with_shared(shared: Option<&str>) -> Self {
+        let mut buffers = Self::load();
+        if let Some(code) = shared {
+            if let Some(existing) = buffers.buffers.iter().find(|b| b.input == code) {
+                buffers.active = existing.id;
+                buffers.save();
+                return buffers;
+            }
+            let id = buffers.add();
+            buffers.active = id;
+            let buffer = buffers.active_mut();
+            buffer.name = "shared".to_string();
+            buffer.input = code.to_string();
+            buffers.save();
+        }
+        buffers
+    }
+
+    /// Removes a buffer (and its runs), unless it's the last one left
+    pub fn remove(&mut self, id: u64) {
+        if self.buffers.len() <= 1 {
+            return;
+        }
+        self.buffers.retain(|b| b.id != id);
+        storage::remove(&runs_key(id));
+        if self.active == id {
+            self.active = self.buffers[0].id;
+        }
+    }
+
+    /// The code of every library buffer the active buffer should see, in tab order
+    pub fn prelude(&self) -> Vec<String> {
+        if self.active().isolated {
+            return vec![];
+        }
+        self.buffers
+            .iter()
+            .filter(|b| b.is_library && b.id != self.active)
+            .map(|b| b.input.clone())
+            .collect()
+    }
+}
+
+fn runs_key(id: u64) -> String {
+    format!("uiuapp-runs-{id}")
+}
+
+/// The scrollback of buffer `id`, as it was last saved
+pub fn load_runs(id: u64, settings: &Settings) -> Vec<RunRecord> {
+    let mut runs: Vec<RunRecord> = storage::load(&runs_key(id)).unwrap_or_default();
+    for run in runs.iter_mut() {
+        run.restore(settings);
+    }
+    runs
+}
+
+/// Saves the scrollback of buffer `id`, with any large output swapped for a short note
+pub fn save_runs(id: u64, runs: &[RunRecord]) -> Result<(), String> {
+    let runs: Vec<RunRecord> = runs.iter().map(persisted).collect();
+    storage::try_save(&runs_key(id), &runs)
+        .map_err(|e| format!("The scrollback could not be saved: {e}"))
+}
+
+/// What's kept of `run` across reloads: the code, errors, and outputs that aren't too large
+fn persisted(run: &RunRecord) -> RunRecord {
+    let mut run = run.clone();
+    for output in run.outputs.iter_mut() {
+        if output.meta.shape.elements() > MAX_PERSISTED_ELEMENTS {
+            let note = format!(
+                "{} {} (too large to keep, run again to see it)",
+                output.meta.type_name, output.meta.shape
+            );
+            output.value = uiua::Value::from(note);
+            output.kind_override = None;
+        }
+    }
+    run
+}
+
+/// Makes `id` the active buffer, bringing in its input and scrollback
+pub fn switch_buffer(
+    mut buffers: Signal<ScratchBuffers>,
+    mut input_contents: Signal<String>,
+    mut buffer_contents: Signal<Vec<RunRecord>>,
+    id: u64,
+    settings: &Settings,
+) {
+    if buffers.peek().active == id {
+        return;
+    }
+    buffers.write().active = id;
+    buffers.peek().save();
+    input_contents.set(buffers.peek().active().input.clone());
+    buffer_contents.set(load_runs(id, settings));
+}
+
+#[test]
+fn large_outputs_are_not_persisted() {
+    let settings = Settings::default();
+    let run = RunRecord::run("⇡1e6 \"small\"", &[], &settings);
+    let kept = persisted(&run);
+    assert_eq!(kept.code, run.code);
+    assert_eq!(kept.outputs[0].value, run.outputs[0].value);
+    assert!(kept.outputs[1].value.show().contains("too large to keep"));
+}
+
+#[test]
+fn libraries_are_loaded_before_other_buffers() {
+    let mut buffers = ScratchBuffers::default();
+    let lib = buffers.add();
+    buffers.buffers[1].input = "Double ← ×2".to_string();
+    buffers.buffers[1].is_library = true;
+    assert_eq!(buffers.prelude(), ["Double ← ×2"]);
+
+    let (result, _) = crate::run_uiua("Double 21", &buffers.prelude(), &Settings::default());
+    assert_eq!(result.unwrap()[0].value, uiua::Value::from(42));
+
+    buffers.active = lib;
+    assert!(
+        buffers.prelude().is_empty(),
+        "a library doesn't load itself"
+    );
+    buffers.active = 0;
+    buffers.active_mut().isolated = true;
+    assert!(buffers.prelude().is_empty());
+}
@@ -0,0 +1,39 @@
+//! The example programs bundled in `assets/examples.json`
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Example {
+    pub topic: String,
+    pub title: String,
+    pub description: String,
+    pub code: String,
+}
+
+lazy_static! {
+    pub static ref examples: Vec<Example> =
+        serde_json::from_str(include_str!("../assets/examples.json"))
+            .expect("the bundled examples should be valid");
+}
+
+/// The examples grouped by topic, with topics in the order they first appear in
+pub fn examples_by_topic() -> Vec<(&'static str, Vec<&'static Example>)> {
+    let mut topics: Vec<(&'static str, Vec<&'static Example>)> = vec![];
+    for example in examples.iter() {
+        match topics.iter_mut().find(|(t, _)| *t == example.topic) {
+            Some((_, group)) => group.push(example),
+            None => topics.push((&example.topic, vec![example])),
+        }
+    }
+    topics
+}
+
+#[test]
+fn examples_run() {
+    let settings = crate::Settings::default();
+    for example in examples.iter() {
+        if let (Err(e), _) = crate::run_uiua(&example.code, &[], &settings) {
+            panic!("Example '{}' fails: {e}", example.title);
+        }
+    }
+}
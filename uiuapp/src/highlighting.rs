@@ -1,6 +1,7 @@
 use crate::*;
+use uiua::lsp::{BindingDocs, BindingDocsKind};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UiuappHistorySpan {
     UnstyledCode {
         text: String,
@@ -8,47 +9,186 @@ pub enum UiuappHistorySpan {
     StyledCode {
         class: String, // HTML/css class
         text: String,  // the actual code
+        bracket: Option<BracketInfo>,
+        info: Option<SpanInfo>,
     },
     Whitspace(String),
 }
 
-/// Returns tuples of (span, text)
-pub fn highlight_code(code: &str) -> Result<Vec<UiuappHistorySpan>, String> {
-    let config = FormatConfig::default();
-    let code = match format_str(code, &config) {
-        Ok(s) => s.output,
-        Err(e) => {
-            return Err(e.to_string());
+/// What the language server knows about a span, shown when it's tapped
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanInfo {
+    pub name: String,
+    pub signature: Option<String>,
+    pub docs: Option<String>,
+}
+
+fn span_info(kind: &SpanKind, text: &str) -> Option<SpanInfo> {
+    match kind {
+        SpanKind::Primitive(p, sub) => Some(SpanInfo {
+            name: match p.glyph() {
+                Some(glyph) => format!("{glyph} {}", p.name()),
+                None => p.name().to_string(),
+            },
+            signature: match p.subscript_sig(*sub) {
+                Some(sig) if sub.is_some() => Some(sig.to_string()),
+                _ => signature_of(p),
+            },
+            docs: Some(p.doc().short_text().into_owned()),
+        }),
+        SpanKind::Ident {
+            docs: Some(docs), ..
+        } => Some(SpanInfo {
+            name: text.trim().to_string(),
+            signature: match &docs.kind {
+                BindingDocsKind::Constant(_) => Some("constant".to_string()),
+                BindingDocsKind::Function { sig, .. } => Some(sig.to_string()),
+                BindingDocsKind::Modifier(args) => Some(format!(
+                    "{args} function{}",
+                    if *args == 1 { "" } else { "s" }
+                )),
+                BindingDocsKind::Module { .. } => Some("module".to_string()),
+                BindingDocsKind::Error => None,
+            },
+            docs: docs.meta.comment.as_ref().map(|c| c.text.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+/// Where a bracket sits in the code's nesting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BracketInfo {
+    /// Shared by a bracket and the one that closes it, unique within the code
+    pub pair: usize,
+    /// How many brackets enclose this one
+    pub depth: usize,
+    /// Whether there's a bracket closing (or opening) this one
+    pub matched: bool,
+}
+
+const OPENING_BRACKETS: &str = "([{⟨";
+const CLOSING_BRACKETS: &str = ")]}⟩";
+
+/// Pairs up the brackets in `spans`, marking the ones that aren't closed (or opened)
+fn match_brackets(spans: &mut [UiuappHistorySpan]) {
+    let mut open: Vec<(usize, char)> = vec![];
+    let mut pairs = 0;
+    for i in 0..spans.len() {
+        let UiuappHistorySpan::StyledCode { text, bracket, .. } = &mut spans[i] else {
+            continue;
+        };
+        let mut chars = text.trim().chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        if OPENING_BRACKETS.contains(c) {
+            *bracket = Some(BracketInfo {
+                pair: pairs,
+                depth: open.len(),
+                matched: false,
+            });
+            open.push((i, c));
+            pairs += 1;
+        } else if let Some(kind) = CLOSING_BRACKETS.find(c) {
+            let opener = open
+                .last()
+                .filter(|(_, o)| OPENING_BRACKETS.find(*o) == Some(kind));
+            match opener.copied() {
+                Some((j, _)) => {
+                    open.pop();
+                    let UiuappHistorySpan::StyledCode {
+                        bracket: Some(info),
+                        ..
+                    } = &mut spans[j]
+                    else {
+                        unreachable!("openers always have bracket info");
+                    };
+                    info.matched = true;
+                    let info = *info;
+                    if let UiuappHistorySpan::StyledCode { bracket, .. } = &mut spans[i] {
+                        *bracket = Some(info);
+                    }
+                }
+                None => {
+                    *bracket = Some(BracketInfo {
+                        pair: pairs,
+                        depth: open.len(),
+                        matched: false,
+                    });
+                    pairs += 1;
+                }
+            }
         }
-    };
+    }
+}
 
-    let mut output: Vec<UiuappHistorySpan> = vec![];
+/// The code that the spans were made from
+pub fn spans_text(spans: &[UiuappHistorySpan]) -> String {
+    spans
+        .iter()
+        .map(|uhs| match uhs {
+            UiuappHistorySpan::UnstyledCode { text } => text.as_str(),
+            UiuappHistorySpan::StyledCode { text, .. } => text.as_str(),
+            UiuappHistorySpan::Whitspace(text) => text.as_str(),
+        })
+        .collect()
+}
 
-    for s in uiua::lsp::Spans::from_input(&code).spans {
-        let text = &code[s.span.start.byte_pos as usize..s.span.end.byte_pos as usize].trim_end();
+/// Formats `code` and splits it into spans, which together cover the formatted code exactly:
+/// text between the lsp's spans (or after the last one) is kept as whitespace or unstyled code
+pub fn highlight_code(code: &str, format: &FormatSettings) -> Vec<UiuappHistorySpan> {
+    // Code that doesn't parse is spanned as it is, so that e.g. its unmatched brackets still show
+    let code = match format_str(code, &format.config()) {
+        Ok(s) => s.output,
+        Err(_) => code.to_string(),
+    };
 
-        let whitespace = (code[0..s.span.start.byte_pos as usize])
-            .chars()
-            .rev()
-            .take_while(|c| c.is_whitespace())
-            .collect::<String>()
-            .chars()
-            .rev()
-            .collect::<String>();
+    let mut output: Vec<UiuappHistorySpan> = vec![];
+    let mut spans = uiua::lsp::Spans::from_input(&code).spans;
+    spans.sort_by_key(|s| s.span.start.byte_pos);
 
-        output.push(UiuappHistorySpan::Whitspace(whitespace));
+    // Everything before `pos` has been turned into spans
+    let mut pos = 0;
+    for s in spans {
+        let start = (s.span.start.byte_pos as usize).max(pos);
+        let end = s.span.end.byte_pos as usize;
+        if end <= start {
+            // Empty, or nested in a span that's already been covered
+            continue;
+        }
+        push_gap(&mut output, &code[pos..start]);
+        let text = code[start..end].to_string();
         output.push(match html_class_of(&s.value) {
             Some(class) => UiuappHistorySpan::StyledCode {
                 class: class.to_string(),
-                text: text.to_string(),
-            },
-            None => UiuappHistorySpan::UnstyledCode {
-                text: text.to_string(),
+                info: span_info(&s.value, &text),
+                text,
+                bracket: None,
             },
+            None => UiuappHistorySpan::UnstyledCode { text },
         });
+        pos = end;
     }
+    push_gap(&mut output, &code[pos..]);
+    match_brackets(&mut output);
 
-    Ok(output)
+    debug_assert_eq!(spans_text(&output), code);
+    output
+}
+
+/// Text no span covers: usually whitespace, but kept as is whatever it is
+fn push_gap(output: &mut Vec<UiuappHistorySpan>, gap: &str) {
+    if gap.is_empty() {
+        return;
+    }
+    output.push(if gap.chars().all(char::is_whitespace) {
+        UiuappHistorySpan::Whitspace(gap.to_string())
+    } else {
+        UiuappHistorySpan::UnstyledCode {
+            text: gap.to_string(),
+        }
+    });
 }
 
 pub fn css_of_prim(p: &P) -> &'static str {
@@ -59,6 +199,13 @@ pub fn css_of_prim(p: &P) -> &'static str {
     ];
     if let Some((_, s)) = special_cased.iter().find(|l| l.0 == *p) {
         s
+    } else if let Some(args) = p.modifier_args() {
+        // Checked first, as some modifiers (like scan) also have a signature
+        match args {
+            1 => "monadic-modifier",
+            2 => "dyadic-modifier",
+            _ => "",
+        }
     } else if let Some(args) = p.args() {
         match args {
             0 => "noadic-function",
@@ -66,12 +213,6 @@ pub fn css_of_prim(p: &P) -> &'static str {
             2 => "dyadic-function",
             _ => "",
         }
-    } else if let Some(args) = p.modifier_args() {
-        match args {
-            1 => "monadic-modifier",
-            2 => "dyadic-modifier",
-            _ => "",
-        }
     } else {
         ""
     }
@@ -92,7 +233,7 @@ fn html_class_of(s: &SpanKind) -> Option<&'static str> {
             } else if s.args() == 2 {
                 Some("dyadic-function")
             } else {
-                None
+                Some("delimiter")
             }
         }
         SpanKind::MacroDelim(s) => {
@@ -101,19 +242,40 @@ fn html_class_of(s: &SpanKind) -> Option<&'static str> {
             } else if *s == 2 {
                 Some("dyadic-modifier")
             } else {
-                None
+                Some("delimiter")
             }
         }
         SpanKind::Subscript(prim, Some(sub)) => html_class_of_prim_sub(*prim, Some(*sub)),
-        SpanKind::Ident { .. }
-        | SpanKind::Label
-        | SpanKind::Signature
-        | SpanKind::Whitespace
-        | SpanKind::Placeholder(_)
-        | SpanKind::Delimiter
-        | SpanKind::LexOrder
-        | SpanKind::ImportSrc(_)
-        | SpanKind::Subscript(_, None) => None,
+        SpanKind::Subscript(_, None) => Some("subscript"),
+        SpanKind::Ident { docs, .. } => Some(html_class_of_binding(docs.as_ref())),
+        SpanKind::Label => Some("label"),
+        SpanKind::Signature => Some("signature"),
+        SpanKind::Placeholder(_) => Some("placeholder"),
+        SpanKind::Delimiter => Some("delimiter"),
+        SpanKind::ImportSrc(_) => Some("import-src"),
+        SpanKind::Whitespace | SpanKind::LexOrder => None,
+    }
+}
+
+/// Bindings are colored like primitives of the same arity, as in the pad
+fn html_class_of_binding(docs: Option<&BindingDocs>) -> &'static str {
+    let Some(docs) = docs else {
+        return "identifier";
+    };
+    match &docs.kind {
+        BindingDocsKind::Constant(_) => "noadic-function",
+        BindingDocsKind::Function { sig, .. } => match sig.args() {
+            0 => "noadic-function",
+            1 => "monadic-function",
+            2 => "dyadic-function",
+            3 => "triadic-function",
+            _ => "tetradic-function",
+        },
+        BindingDocsKind::Modifier(1) => "monadic-modifier",
+        BindingDocsKind::Modifier(2) => "dyadic-modifier",
+        BindingDocsKind::Modifier(_) => "triadic-modifier",
+        BindingDocsKind::Module { .. } => "module-name",
+        BindingDocsKind::Error => "identifier",
     }
 }
 fn html_class_of_prim(prim: P, args: Option<usize>) -> Option<&'static str> {
@@ -123,11 +285,11 @@ fn html_class_of_prim(prim: P, args: Option<usize>) -> Option<&'static str> {
     } else if matches!(prim.class(), PrimClass::Stack | PrimClass::Debug) || prim == P::Identity {
         Some("stack-function")
     } else if let Some(args) = prim.modifier_args() {
-        return if args == 1 {
+        if args == 1 {
             Some("monadic-modifier")
         } else {
             Some("dyadic-modifier")
-        };
+        }
     } else if let Some(args) = args.or(prim.sig().map(|sig| sig.args())) {
         match args {
             0 => Some("noadic-function"),
@@ -147,3 +309,130 @@ fn html_class_of_prim_sub(prim: Option<P>, sub: Option<Subscript>) -> Option<&'s
     prim.map(|prim| html_class_of_prim(prim, args))
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+fn assert_round_trips(code: &str) {
+    let format = FormatSettings::default();
+    let formatted = format_code(code, &format).unwrap();
+    let spans = highlight_code(code, &format);
+    assert_eq!(spans_text(&spans), formatted, "spans of {code:?}");
+}
+
+#[test]
+fn highlighting_round_trips_simple_code() {
+    assert_round_trips("+1 2");
+    assert_round_trips("⇌⇡10");
+    assert_round_trips("reverse range 10");
+    assert_round_trips("[1 2 3]");
+}
+
+#[test]
+fn highlighting_round_trips_strings() {
+    assert_round_trips(r#""hello, world""#);
+    assert_round_trips(r#"$"_ and _" 1 2"#);
+    assert_round_trips("@a @\\n");
+    assert_round_trips(r#"⊂ "a  b" "c""#);
+}
+
+#[test]
+fn highlighting_round_trips_comments() {
+    assert_round_trips("+1 2 # a comment");
+    assert_round_trips("# only a comment");
+    assert_round_trips("⇡3\n##");
+}
+
+#[test]
+fn highlighting_round_trips_multiline_code() {
+    assert_round_trips("F ← +1\nF 2");
+    assert_round_trips("G ← (\n  +1\n  ×2\n)\nG 3");
+    assert_round_trips("x ← 1\n\ny ← 2");
+    assert_round_trips("$ multi-line\n$ string\n&p");
+    assert_round_trips("# two\n# comments\n+1 1");
+}
+
+#[test]
+fn highlighting_round_trips_subscripts() {
+    assert_round_trips("⊟₃ 1 2 3");
+    assert_round_trips("×₂ 4");
+    assert_round_trips("⍥₃(+1) 0");
+}
+
+#[test]
+fn highlighting_round_trips_unicode() {
+    assert_round_trips(r#""ünïcödé ✓ 🦀""#);
+    assert_round_trips("@🦀");
+    assert_round_trips("⊞× . ÷⟜⇡ 100");
+    assert_round_trips("Ä ← 5\nÄ");
+}
+
+#[test]
+fn unparsable_code_still_shows_its_brackets() {
+    let code = "/+ (×2 [1 2 3]";
+    assert!(format_code(code, &FormatSettings::default()).is_err());
+    let spans = highlight_code(code, &FormatSettings::default());
+    assert_eq!(spans_text(&spans), code);
+    let brackets: Vec<(String, bool)> = spans
+        .into_iter()
+        .filter_map(|span| match span {
+            UiuappHistorySpan::StyledCode {
+                text,
+                bracket: Some(b),
+                ..
+            } => Some((text, b.matched)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        brackets,
+        [
+            ("(".to_string(), false),
+            ("[".to_string(), true),
+            ("]".to_string(), true)
+        ]
+    );
+}
+
+#[test]
+fn spans_know_what_they_are() {
+    let spans = highlight_code("# Adds one\nF ← +1\nF ⇌ 2", &FormatSettings::default());
+    let infos: Vec<SpanInfo> = spans
+        .into_iter()
+        .filter_map(|span| match span {
+            UiuappHistorySpan::StyledCode { info, .. } => info,
+            _ => None,
+        })
+        .collect();
+    let reverse = infos.iter().find(|i| i.name == "⇌ reverse").unwrap();
+    assert_eq!(reverse.signature.as_deref(), Some("|1.1"));
+    let f = infos.iter().rfind(|i| i.name == "F").unwrap();
+    assert_eq!(f.signature.as_deref(), Some("|1.1"));
+    assert_eq!(f.docs.as_deref(), Some("Adds one"));
+}
+
+#[test]
+fn primitives_get_the_class_of_their_category() {
+    let class = |p: P| html_class_of(&SpanKind::Primitive(p, None));
+    assert_eq!(class(P::Dup), Some("stack-function"));
+    assert_eq!(class(P::Identity), Some("stack-function"));
+    assert_eq!(class(P::Rand), Some("noadic-function"));
+    assert_eq!(class(P::Neg), Some("monadic-function"));
+    assert_eq!(class(P::Add), Some("dyadic-function"));
+    assert_eq!(class(P::Reduce), Some("monadic-modifier"));
+    assert_eq!(class(P::Fork), Some("dyadic-modifier"));
+    assert_eq!(class(P::Transpose), Some("prim-trans"));
+    assert_eq!(class(P::Both), Some("prim-both"));
+    assert_eq!(html_class_of(&SpanKind::String), Some("string-literal"));
+    assert_eq!(html_class_of(&SpanKind::Number), Some("constant-value"));
+    assert_eq!(html_class_of(&SpanKind::Comment), Some("comment"));
+    assert_eq!(html_class_of(&SpanKind::OutputComment), Some("comment"));
+    assert_eq!(html_class_of(&SpanKind::Whitespace), None);
+
+    // The keypad and the highlighter agree on every plain function and modifier
+    for p in P::non_deprecated() {
+        let keypad = css_of_prim(&p);
+        let is_stack = matches!(p.class(), PrimClass::Stack | PrimClass::Debug);
+        if !keypad.is_empty() && !is_stack {
+            assert_eq!(class(p), Some(keypad), "{}", p.name());
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_KEY: &str = "uiuapp-history";
+/// Oldest entries are forgotten past this point
+const MAX_HISTORY_LEN: usize = 500;
+
+/// Every piece of code that's been run, navigable like a shell's history
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputHistory {
+    pub entries: Vec<String>,
+    /// Which entry is currently in the input bar, `None` while editing the draft
+    #[serde(skip)]
+    cursor: Option<usize>,
+    /// Whatever was being typed before navigating away from it
+    #[serde(skip)]
+    draft: String,
+}
+
+impl InputHistory {
+    pub fn load() -> Self {
+        storage::load(HISTORY_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(HISTORY_KEY, self);
+    }
+
+    pub fn push(&mut self, code: &str) {
+        self.cursor = None;
+        self.draft.clear();
+        if code.trim().is_empty() || self.entries.last().is_some_and(|last| last == code) {
+            return;
+        }
+        self.entries.push(code.to_string());
+        if self.entries.len() > MAX_HISTORY_LEN {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Steps to an older entry. `current` is what's in the input bar, which is kept
+    /// as the draft when starting to navigate
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let cursor = match self.cursor {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(c) => c.saturating_sub(1),
+        };
+        self.cursor = Some(cursor);
+        Some(self.entries[cursor].clone())
+    }
+
+    /// Steps to a newer entry, eventually returning to the draft
+    pub fn newer(&mut self) -> Option<String> {
+        let cursor = self.cursor?;
+        if cursor + 1 < self.entries.len() {
+            self.cursor = Some(cursor + 1);
+            Some(self.entries[cursor + 1].clone())
+        } else {
+            self.cursor = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    /// Entries containing `query`, newest first and without repeats
+    pub fn search(&self, query: &str) -> Vec<String> {
+        let mut found: Vec<String> = vec![];
+        for entry in self.entries.iter().rev() {
+            if entry.contains(query) && !found.contains(entry) {
+                found.push(entry.clone());
+            }
+        }
+        found
+    }
+}
+
+#[test]
+fn history_navigation_keeps_draft() {
+    let mut h = InputHistory::default();
+    h.push("1");
+    h.push("2");
+    h.push("2");
+    assert_eq!(h.entries, ["1", "2"]);
+    assert_eq!(h.newer(), None);
+    assert_eq!(h.older("dra").as_deref(), Some("2"));
+    assert_eq!(h.older("2").as_deref(), Some("1"));
+    assert_eq!(h.older("1").as_deref(), Some("1"));
+    assert_eq!(h.newer().as_deref(), Some("2"));
+    assert_eq!(h.newer().as_deref(), Some("dra"));
+    assert_eq!(h.newer(), None);
+}
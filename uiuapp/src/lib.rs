@@ -1,15 +1,36 @@
+pub mod array_view;
+pub mod buffers;
+pub mod examples;
 pub mod highlighting;
+pub mod history;
 pub mod multimedia;
+pub mod reference;
+pub mod session;
+pub mod share;
+pub mod storage;
+pub mod timing;
 pub mod ui;
+pub use array_view::*;
+pub use buffers::*;
+pub use examples::*;
 pub use highlighting::*;
+pub use history::*;
+pub use reference::*;
+pub use timing::*;
 pub use ui::*;
 
 use dioxus::{
     html::geometry::{euclid::Point2D, ScreenSpace},
     prelude::*,
 };
+use dioxus_logger::tracing::warn;
 use lazy_static::lazy_static;
-use std::{f32::consts::PI, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::PI,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 use uiua::{
     ast::Subscript,
     format::{format_str, FormatConfig},
@@ -26,59 +47,422 @@ use Either as E;
 /// An icon is either
 /// - (A vector of) Primitives
 /// - A string (and its associated html class)
+///
 /// (Primitives do not store their class themselves, its computed based on their signature)
 ///
 /// Primitives are stored as a vector to support multi-primitive icons, like `wrench` (subbyneg)
 pub type ButtonIcon = Either<Vec<P>, (&'static str, &'static str)>;
 
 pub const TAU: f32 = 2.0 * PI;
+/// Default for [Settings::max_output_chars]
 pub const MAX_OUTPUT_CHARS: usize = 1000;
 pub const UNKNOWN_GLYPH: char = '¡';
 pub const EXPERIMENTAL_ICON: &str = "🧪";
+
+/// Shown to first-time users as (what it is, code), one of each kind of output
+pub const ONBOARDING_EXAMPLES: [(&str, &str); 3] = [
+    ("An image", "⊞× . ÷⟜⇡ 100"),
+    ("A sound", "÷4 ∿×τ×220 ÷⟜⇡44100"),
+    ("An array", "⊞+ . ⇡5"),
+];
 const DEADZONE_RADIUS: f64 = 30.;
 
-#[derive(Debug, Clone)]
-pub enum ScrollbackItem {
-    Input(Result<Vec<UiuappHistorySpan>, String>),
-    Output(Vec<ScrollbackOutput>),
+/// One run of some code: what was run and everything that came out of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Stable across re-runs, unlike the record's position in the scrollback
+    pub id: u64,
+    pub code: String,
+    /// Not persisted, see [RunRecord::restore]
+    #[serde(skip)]
+    pub spans: Vec<UiuappHistorySpan>,
+    pub outputs: Vec<ScrollbackOutput>,
+    pub error: Option<String>,
+    /// When the run started, in milliseconds since the unix epoch
+    pub timestamp: f64,
+    pub timings: RunTimings,
+    /// Only there when the run was benchmarked, see [Settings::benchmark_runs]
+    pub benchmark: Option<BenchmarkStats>,
+}
+
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(0);
+
+impl RunRecord {
+    /// Runs `code` after loading the code in `prelude`, e.g. the definitions in library buffers
+    pub fn run(code: &str, prelude: &[String], settings: &Settings) -> Self {
+        let mut record = Self {
+            id: NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed),
+            code: String::new(),
+            spans: vec![],
+            outputs: vec![],
+            error: None,
+            timestamp: 0.,
+            timings: RunTimings::default(),
+            benchmark: None,
+        };
+        record.rerun(code, prelude, settings);
+        record
+    }
+
+    /// Runs `code` in place of whatever this record held, keeping its id
+    pub fn rerun(&mut self, code: &str, prelude: &[String], settings: &Settings) {
+        self.code = code.to_string();
+        self.spans = highlight_code(code, &settings.format);
+        self.timestamp = now_ms();
+        let (result, timings) = run_uiua(code, prelude, settings);
+        self.timings = timings;
+        match result {
+            Ok(outputs) => {
+                self.outputs = outputs;
+                self.error = None;
+            }
+            Err(e) => {
+                self.outputs = vec![];
+                self.error = Some(e);
+            }
+        }
+
+        self.benchmark = None;
+        if settings.benchmark_runs > 1 && self.error.is_none() {
+            let mut times = vec![timings.total()];
+            times.extend(
+                (1..settings.benchmark_runs).map(|_| run_uiua_once(code, prelude).1.total()),
+            );
+            self.benchmark = BenchmarkStats::of(times);
+        }
+    }
+
+    /// Rebuilds what isn't persisted after the record has been loaded from storage
+    pub fn restore(&mut self, settings: &Settings) {
+        self.spans = highlight_code(&self.code, &settings.format);
+        for output in self.outputs.iter_mut() {
+            output.rerender(settings);
+        }
+        NEXT_RUN_ID.fetch_max(self.id + 1, Ordering::Relaxed);
+    }
+}
+
+/// A value that was left on the stack, along with how it's currently being shown.
+/// The value is kept around so that it can be re-rendered as some other [OutputKind]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrollbackOutput {
+    pub value: uiua::Value,
+    /// Not persisted, as it can always be encoded again from the value
+    #[serde(skip)]
+    pub media: OutputMedia,
+    pub meta: ValueMeta,
+    /// Set when the user picked a kind by hand, so that it survives re-rendering
+    pub kind_override: Option<OutputKind>,
+}
+
+/// What's shown in the caption of each stack value
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ValueMeta {
+    pub shape: uiua::Shape,
+    pub type_name: String,
+    pub label: Option<String>,
+    /// How far from the top of the stack the value was, `0` being the top
+    pub depth: usize,
+    /// How many values were on the stack alongside this one
+    pub stack_size: usize,
 }
 
-#[derive(Debug, Clone)]
-pub enum ScrollbackOutput {
+impl ValueMeta {
+    pub fn of(value: &uiua::Value) -> Self {
+        Self {
+            shape: value.shape.clone(),
+            type_name: value.type_name().to_string(),
+            label: value.meta().label.as_ref().map(|l| l.to_string()),
+            depth: 0,
+            stack_size: 1,
+        }
+    }
+
+    /// The value's position as counted from whichever end of the stack is drawn first
+    pub fn position(&self, ordering: &StackOrdering) -> String {
+        match ordering {
+            StackOrdering::TopAtTop => format!("↓{}", self.depth),
+            StackOrdering::BottomAtTop => format!("↑{}", self.stack_size - 1 - self.depth),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputMedia {
     Text(String),
     Image(Vec<u8>),
     Gif(Vec<u8>),
     Audio(Vec<u8>),
 }
 
-#[derive(Debug, Clone)]
+impl Default for OutputMedia {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputKind {
+    Text,
+    Image,
+    Audio,
+    Gif,
+}
+
+impl OutputKind {
+    pub const ALL: [OutputKind; 4] = [Self::Text, Self::Image, Self::Audio, Self::Gif];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Gif => "gif",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub clean_input_on_run: bool,
-    pub execution_limit: Duration,         // TODO: make it do something
-    pub audio_sample_time: u32,            // TODO: make it do something
-    pub autoplay_video: bool,              // TODO: make it do something
-    pub autoplay_audio: bool,              // TODO: make it do something
-    pub gayness: (),                       // TODO: make it do something
-    pub stack_ordering: StackOrdering,     // TODO: make it do something
+    pub execution_limit: Duration, // TODO: make it do something
+    pub audio_sample_time: u32,
+    pub media: MediaSettings,
+    /// Draw text outputs as structured arrays instead of uiua's own formatting
+    pub pretty_arrays: bool,
+    /// Text outputs longer than this are collapsed into a preview
+    pub max_output_chars: usize,
+    /// When above 1, every run is repeated this many times to report how long it takes
+    pub benchmark_runs: usize,
+    /// Format the input in place before running it, which turns names into glyphs
+    pub format_on_run: bool,
+    /// Turn primitive names into glyphs as soon as they're typed
+    pub live_glyphs: bool,
+    /// Color brackets by how deeply they're nested
+    pub rainbow_brackets: bool,
+    pub format: FormatSettings,
+    pub autoplay_video: bool, // TODO: make it do something
+    pub autoplay_audio: bool, // TODO: make it do something
+    /// Glyphs colored after a pride flag, like the pad can do
+    pub gayness: PrideFlag,
+    pub theme: Theme,
+    pub stack_ordering: StackOrdering,
     pub font_size: f32,                    // TODO: make it do something
     pub stack_preserved_across_runs: bool, // TODO: make it do something
 }
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub enum StackOrdering {
     #[default]
     BottomAtTop,
     TopAtTop,
 }
 
+/// The palette of the whole app, highlighting included
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// Light or dark, whichever the system prefers
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+    ColorblindSafe,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 5] = [
+        Theme::System,
+        Theme::Light,
+        Theme::Dark,
+        Theme::HighContrast,
+        Theme::ColorblindSafe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+            Theme::ColorblindSafe => "Colorblind-safe",
+        }
+    }
+
+    /// [Theme::System] as whichever of light or dark the system prefers, any other theme as is
+    pub fn resolved(self) -> Theme {
+        if self != Theme::System {
+            return self;
+        }
+        let prefers_light = web_sys::window()
+            .and_then(|w| w.match_media("(prefers-color-scheme: light)").ok()?)
+            .is_some_and(|query| query.matches());
+        if prefers_light {
+            Theme::Light
+        } else {
+            Theme::Dark
+        }
+    }
+
+    /// The value of the app's `data-theme` attribute (once [resolved](Theme::resolved)),
+    /// which the css selects on
+    pub fn attr(&self) -> &'static str {
+        match self {
+            Theme::System => "system",
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+            Theme::ColorblindSafe => "colorblind-safe",
+        }
+    }
+}
+
+/// A flag whose stripes glyphs are drawn with, instead of their usual color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrideFlag {
+    #[default]
+    Off,
+    Rainbow,
+    Trans,
+    Bi,
+    Pan,
+    Lesbian,
+    NonBinary,
+    Ace,
+}
+
+impl PrideFlag {
+    pub const ALL: [PrideFlag; 8] = [
+        PrideFlag::Off,
+        PrideFlag::Rainbow,
+        PrideFlag::Trans,
+        PrideFlag::Bi,
+        PrideFlag::Pan,
+        PrideFlag::Lesbian,
+        PrideFlag::NonBinary,
+        PrideFlag::Ace,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrideFlag::Off => "Off",
+            PrideFlag::Rainbow => "Rainbow",
+            PrideFlag::Trans => "Trans",
+            PrideFlag::Bi => "Bi",
+            PrideFlag::Pan => "Pan",
+            PrideFlag::Lesbian => "Lesbian",
+            PrideFlag::NonBinary => "Non-binary",
+            PrideFlag::Ace => "Ace",
+        }
+    }
+
+    /// The classes put on the app, which recolor every glyph class inside it
+    pub fn class(&self) -> &'static str {
+        match self {
+            PrideFlag::Off => "",
+            PrideFlag::Rainbow => "pride pride-rainbow",
+            PrideFlag::Trans => "pride pride-trans",
+            PrideFlag::Bi => "pride pride-bi",
+            PrideFlag::Pan => "pride pride-pan",
+            PrideFlag::Lesbian => "pride pride-lesbian",
+            PrideFlag::NonBinary => "pride pride-nonbinary",
+            PrideFlag::Ace => "pride pride-ace",
+        }
+    }
+}
+
+/// Thresholds used when guessing whether a value is media
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MediaSettings {
+    /// Smallest width and height for a value to be shown as an image or gif
+    pub min_image_dim: usize,
+    pub gif_fps: f64,
+    pub gif_min_frames: usize,
+    pub detect_audio: bool,
+}
+
+impl Default for MediaSettings {
+    fn default() -> Self {
+        Self {
+            min_image_dim: 30,
+            gif_fps: 16.0,
+            gif_min_frames: 5,
+            detect_audio: true,
+        }
+    }
+}
+
+const SETTINGS_KEY: &str = "uiuapp-settings";
+
+impl Settings {
+    pub fn load() -> Self {
+        storage::load(SETTINGS_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(SETTINGS_KEY, self);
+    }
+}
+
+/// The options of uiua's formatter, used both to display code and to format the input
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FormatSettings {
+    pub multiline_indent: usize,
+    pub trailing_newline: bool,
+    pub comment_space_after_hash: bool,
+    pub align_comments: bool,
+    pub indent_item_imports: bool,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        let config = FormatConfig::default();
+        Self {
+            multiline_indent: config.multiline_indent,
+            trailing_newline: config.trailing_newline,
+            comment_space_after_hash: config.comment_space_after_hash,
+            align_comments: config.align_comments,
+            indent_item_imports: config.indent_item_imports,
+        }
+    }
+}
+
+impl FormatSettings {
+    pub fn config(&self) -> FormatConfig {
+        FormatConfig::default()
+            .with_multiline_indent(self.multiline_indent)
+            .with_trailing_newline(self.trailing_newline)
+            .with_comment_space_after_hash(self.comment_space_after_hash)
+            .with_align_comments(self.align_comments)
+            .with_indent_item_imports(self.indent_item_imports)
+    }
+}
+
+/// Formats `code` the way the settings say, e.g. to turn names into glyphs
+pub fn format_code(code: &str, format: &FormatSettings) -> Result<String, String> {
+    format_str(code, &format.config())
+        .map(|formatted| formatted.output)
+        .map_err(|e| e.to_string())
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             clean_input_on_run: false,
             execution_limit: Duration::from_secs(5),
             audio_sample_time: 44100,
+            media: MediaSettings::default(),
+            pretty_arrays: true,
+            max_output_chars: MAX_OUTPUT_CHARS,
+            benchmark_runs: 0,
+            format_on_run: false,
+            live_glyphs: false,
+            rainbow_brackets: false,
+            format: FormatSettings::default(),
             autoplay_video: false,
             autoplay_audio: false,
-            gayness: (),
+            gayness: PrideFlag::default(),
+            theme: Theme::default(),
             stack_ordering: StackOrdering::default(),
             font_size: 100.0,                  // TODO: implement
             stack_preserved_across_runs: true, // TODO: implement
@@ -86,54 +470,318 @@ impl Default for Settings {
     }
 }
 
-pub fn run_uiua(code: &str) -> Result<Vec<ScrollbackOutput>, String> {
-    let mut runtime = uiua::Uiua::with_safe_sys();
-    match runtime.compile_run(|comp| comp.experimental(true).load_str(code)) {
-        Ok(_compiler) => Ok(runtime
-            .take_stack()
+/// Compiles and runs `code` (after `prelude`), timing each step separately
+pub fn run_uiua(
+    code: &str,
+    prelude: &[String],
+    settings: &Settings,
+) -> (Result<Vec<ScrollbackOutput>, String>, RunTimings) {
+    let (result, timings) = run_uiua_once(code, prelude);
+    let outputs = result.map(|stack| {
+        let stack_size = stack.len();
+        stack
             .into_iter()
-            .map(|v| ScrollbackOutput::from_uiuavalue(v, ()))
-            .collect()),
-        Err(e) => Err(e.to_string()),
+            .enumerate()
+            .map(|(i, v)| {
+                let mut output = ScrollbackOutput::from_uiuavalue(v, settings);
+                output.meta.depth = stack_size - 1 - i;
+                output.meta.stack_size = stack_size;
+                output
+            })
+            .collect()
+    });
+    (outputs, timings)
+}
+
+/// Runs `code` and gives back its raw stack, without rendering anything
+fn run_uiua_once(code: &str, prelude: &[String]) -> (Result<Vec<uiua::Value>, String>, RunTimings) {
+    let mut timings = RunTimings::default();
+
+    let start = performance_now();
+    let mut comp = uiua::Compiler::with_backend(uiua::SafeSys::default());
+    comp.experimental(true);
+    let mut loaded = Ok(());
+    for src in prelude.iter().map(String::as_str).chain([code]) {
+        if let Err(e) = comp.load_str(src) {
+            loaded = Err(e.to_string());
+            break;
+        }
+    }
+    let compiled = loaded.map(|()| comp.finish());
+    timings.compile = elapsed_since(start);
+    let asm = match compiled {
+        Ok(asm) => asm,
+        Err(e) => return (Err(e), timings),
+    };
+
+    let start = performance_now();
+    let mut runtime = uiua::Uiua::with_safe_sys();
+    let ran = runtime.run_asm(asm);
+    timings.execution = elapsed_since(start);
+    match ran {
+        Ok(()) => (Ok(runtime.take_stack()), timings),
+        Err(e) => (Err(e.to_string()), timings),
+    }
+}
+
+/// Fills each `##` line of `code` with the stack at that point, like the native formatter does
+///
+/// The code is formatted along the way, so names also become glyphs.
+/// The `prelude` is evaluated first, as when running, but left out of the result.
+pub fn evaluate_output_comments(
+    code: &str,
+    prelude: &[String],
+    format: &FormatSettings,
+) -> Result<String, String> {
+    const PRELUDE_END: &str = "# end of the library buffers";
+    let config = FormatConfig {
+        backend: Some(std::sync::Arc::new(uiua::SafeSys::default())),
+        ..format.config()
+    };
+    let mut source = String::new();
+    for lib in prelude {
+        source.push_str(lib);
+        source.push('\n');
+    }
+    if !prelude.is_empty() {
+        source.push_str(PRELUDE_END);
+        source.push('\n');
     }
+    source.push_str(code);
+    let formatted = format_str(&source, &config)
+        .map_err(|e| e.to_string())?
+        .output;
+    let own = if prelude.is_empty() {
+        formatted.as_str()
+    } else {
+        let (_, own) = formatted
+            .split_once(&format!("{PRELUDE_END}\n"))
+            .ok_or("Could not tell the library buffers apart from the input")?;
+        own
+    };
+    Ok(own.trim_end().to_string())
 }
 
 pub fn handle_running_code(
     mut input_contents: Signal<String>,
-    mut buffer_contents: Signal<Vec<ScrollbackItem>>,
+    buffer_contents: Signal<Vec<RunRecord>>,
+    history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
     settings: Signal<Settings>,
 ) {
-    use ScrollbackItem as SBI;
-    buffer_contents
-        .write()
-        .push(SBI::Input(highlight_code(&input_contents.read().clone())));
-    match run_uiua(&input_contents()) {
-        Ok(sbo) => {
-            let s = sbo
-                .into_iter()
-                .map(|s| match s {
-                    ScrollbackOutput::Text(ref text) if text.len() > MAX_OUTPUT_CHARS => {
-                        ScrollbackOutput::Text(
-                            text.chars()
-                                .take(MAX_OUTPUT_CHARS)
-                                .chain(vec!['.', '.', '.'].into_iter())
-                                .collect(),
-                        )
-                    }
-                    x => x,
-                })
-                .collect();
-
-            buffer_contents.write().push(SBI::Output(s));
-            if settings.read().clean_input_on_run {
-                *input_contents.write() = String::new();
-            }
+    let mut code = input_contents();
+    if settings.read().format_on_run {
+        // Code that doesn't parse is run as is, to show the error
+        if let Ok(formatted) = format_code(&code, &settings.read().format) {
+            input_contents.set(formatted.clone());
+            code = formatted;
         }
-        Err(s) => {
-            buffer_contents
-                .write()
-                .push(SBI::Output(vec![ScrollbackOutput::Text(s)]));
-            *input_contents.write() = String::new();
+    }
+    let failed = run_into_scrollback(&code, buffer_contents, history, buffers, settings);
+    if failed || settings.read().clean_input_on_run {
+        *input_contents.write() = String::new();
+    }
+}
+
+/// Runs `code` as a new entry at the end of the scrollback, returning whether it failed
+pub fn run_into_scrollback(
+    code: &str,
+    mut buffer_contents: Signal<Vec<RunRecord>>,
+    mut history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+) -> bool {
+    history.write().push(code);
+    history.read().save();
+    let prelude = buffers.read().prelude();
+    let record = RunRecord::run(code, &prelude, &settings.read());
+    let failed = record.error.is_some();
+    buffer_contents.write().push(record);
+    failed
+}
+
+/// Runs the record with the given id again, with `code` in place of its old code
+pub fn rerun_entry(
+    mut buffer_contents: Signal<Vec<RunRecord>>,
+    id: u64,
+    code: &str,
+    buffers: Signal<ScratchBuffers>,
+    settings: &Settings,
+) {
+    let prelude = buffers.read().prelude();
+    if let Some(record) = buffer_contents.write().iter_mut().find(|r| r.id == id) {
+        record.rerun(code, &prelude, settings);
+    }
+}
+
+pub fn delete_entry(mut buffer_contents: Signal<Vec<RunRecord>>, id: u64) {
+    buffer_contents.write().retain(|r| r.id != id);
+}
+
+pub fn copy_to_clipboard(text: &str) {
+    match web_sys::window() {
+        // The promise is fire-and-forget, there's nothing to do once it resolves
+        Some(window) => drop(window.navigator().clipboard().write_text(text)),
+        None => warn!("No window to copy to the clipboard from"),
+    }
+}
+
+/// The id of the main input's textarea
+pub const INPUT_ID: &str = "input-text-box";
+
+fn textarea(id: &str) -> Option<web_sys::HtmlTextAreaElement> {
+    use web_sys::wasm_bindgen::JsCast;
+    let element = web_sys::window()?.document()?.get_element_by_id(id)?;
+    element.dyn_into().ok()
+}
+
+/// Where the caret is in textarea `id`, in utf-16 code units as the browser counts them
+pub fn caret_position(id: &str) -> Option<usize> {
+    textarea(id)?
+        .selection_start()
+        .ok()?
+        .map(|pos| pos as usize)
+}
+
+/// Replaces the text of textarea `id` and puts the caret at `caret` (in utf-16 code units).
+/// Done on the element itself, as the caret would otherwise end up at the end on re-render
+pub fn set_text_and_caret(id: &str, text: &str, caret: usize) {
+    let Some(textarea) = textarea(id) else {
+        return;
+    };
+    textarea.set_value(text);
+    let caret = caret as u32;
+    if textarea.set_selection_range(caret, caret).is_err() {
+        warn!("Could not move the caret of '{id}'");
+    }
+}
+
+/// If the caret (a utf-16 offset) just ended a primitive's name with a space or delimiter,
+/// the text with that name turned into its glyph, along with where the caret should now be
+pub fn glyphify_at_caret(
+    text: &str,
+    caret: usize,
+    format: &FormatSettings,
+) -> Option<(String, usize)> {
+    let mut units = 0;
+    let caret_byte = text
+        .char_indices()
+        .find(|(_, c)| {
+            let found = units >= caret;
+            units += c.len_utf16();
+            found
+        })
+        .map_or(text.len(), |(i, _)| i);
+    let before = &text[..caret_byte];
+    let ender = before.chars().next_back()?;
+    if !(ender.is_whitespace() || "()[]{}|;_".contains(ender)) {
+        return None;
+    }
+    let word_end = caret_byte - ender.len_utf8();
+    let word_start = before[..word_end]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphabetic())
+        .last()
+        .map(|(i, _)| i)?;
+    let word = &text[word_start..word_end];
+    // Capitalized words are bindings, never primitives
+    if !word.starts_with(char::is_lowercase) {
+        return None;
+    }
+    // Only code is rewritten, never names inside strings or comments
+    let spans = uiua::lsp::Spans::from_input(text).spans;
+    let covering: Vec<&SpanKind> = spans
+        .iter()
+        .filter(|s| {
+            (s.span.start.byte_pos as usize) < word_end
+                && (s.span.end.byte_pos as usize) > word_start
+        })
+        .map(|s| &s.value)
+        .collect();
+    if covering.is_empty()
+        || !covering
+            .iter()
+            .all(|k| matches!(k, SpanKind::Primitive(..)))
+    {
+        return None;
+    }
+    let glyphs = format_str(word, &format.config()).ok()?.output;
+    let glyphs = glyphs.trim();
+    // Greek letter glyphs like `π` are alphabetic too, so only an unchanged word is left alone
+    if glyphs.is_empty() || glyphs == word {
+        return None;
+    }
+    let new_text = format!("{}{glyphs}{}", &text[..word_start], &text[word_end..]);
+    let new_caret = text[..word_start].encode_utf16().count()
+        + glyphs.encode_utf16().count()
+        + ender.len_utf16();
+    Some((new_text, new_caret))
+}
+
+/// Re-renders every output in the scrollback, e.g. after the media settings changed.
+/// Outputs whose kind was picked by hand keep it
+pub fn rerender_scrollback(mut buffer_contents: Signal<Vec<RunRecord>>, settings: &Settings) {
+    for record in buffer_contents.write().iter_mut() {
+        for output in record.outputs.iter_mut() {
+            output.rerender(settings);
+        }
+    }
+}
+
+/// Highlights the scrollback's code again, e.g. after the formatter settings changed
+pub fn rehighlight_scrollback(mut buffer_contents: Signal<Vec<RunRecord>>, settings: &Settings) {
+    for record in buffer_contents.write().iter_mut() {
+        record.spans = highlight_code(&record.code, &settings.format);
+    }
+}
+
+/// A preview of `text` that's at most `limit` characters long, or `None` if it already fits.
+/// Whole rows are kept when possible, so that matrices don't get cut mid-row
+pub fn truncate_output(text: &str, limit: usize) -> Option<String> {
+    if text.chars().count() <= limit {
+        return None;
+    }
+    let mut preview = String::new();
+    let mut taken = 0;
+    for line in text.lines() {
+        let len = line.chars().count() + 1;
+        if taken + len > limit {
+            break;
+        }
+        preview.push_str(line);
+        preview.push('\n');
+        taken += len;
+    }
+    if preview.is_empty() {
+        // A single huge row, nothing better to do than to cut it
+        preview = text.chars().take(limit).collect();
+        preview.push('…');
+    } else {
+        preview.push('⋮');
+    }
+    Some(preview)
+}
+
+/// How a text output is first shown in the scrollback
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextDisplay {
+    /// As a structured [ArrayView]
+    Pretty,
+    /// As uiua formats it
+    Plain,
+    /// Cut down to a preview, until it's tapped
+    Collapsed(String),
+}
+
+impl TextDisplay {
+    /// Long outputs are collapsed whether they'd be pretty or not, so that huge
+    /// values never make it into the page whole
+    pub fn of(text: &str, pretty: bool, max_chars: usize) -> Self {
+        match truncate_output(text, max_chars) {
+            Some(preview) => Self::Collapsed(preview),
+            None if pretty => Self::Pretty,
+            None => Self::Plain,
         }
     }
 }
@@ -259,6 +907,87 @@ lazy_static! {
     ];
 }
 
+#[test]
+fn formatting_follows_the_settings() {
+    let mut format = FormatSettings::default();
+    assert_eq!(format_code("reverse range 3", &format).unwrap(), "⇌ ⇡ 3\n");
+    format.trailing_newline = false;
+    assert_eq!(format_code("reverse range 3", &format).unwrap(), "⇌ ⇡ 3");
+}
+
+#[test]
+fn names_become_glyphs_at_the_caret() {
+    let format = FormatSettings::default();
+    let text = "⇡3 reverse x";
+    let caret = "⇡3 reverse ".encode_utf16().count();
+    assert_eq!(
+        glyphify_at_caret(text, caret, &format),
+        Some(("⇡3 ⇌ x".to_string(), "⇡3 ⇌ ".encode_utf16().count()))
+    );
+    assert_eq!(
+        glyphify_at_caret("×2 pi ", 6, &format),
+        Some(("×2 π ".to_string(), 5))
+    );
+    assert_eq!(glyphify_at_caret("reverse", 7, &format), None);
+    assert_eq!(glyphify_at_caret("Reverse ", 8, &format), None);
+    assert_eq!(glyphify_at_caret("x ", 2, &format), None);
+    assert_eq!(glyphify_at_caret("# reverse ", 10, &format), None);
+    assert_eq!(glyphify_at_caret("\"reverse ", 9, &format), None);
+    assert_eq!(glyphify_at_caret("$ reverse ", 10, &format), None);
+}
+
+#[test]
+fn output_comments_show_the_stack() {
+    let format = FormatSettings::default();
+    let filled = evaluate_output_comments("⇡3\n##\n+1 1 ##", &[], &format).unwrap();
+    assert_eq!(filled, "⇡3\n## [0 1 2]\n+1 1 ## 2");
+
+    let prelude = ["Double ← ×2".to_string()];
+    let filled = evaluate_output_comments("Double 21 ##", &prelude, &format).unwrap();
+    assert_eq!(filled, "Double 21 ## 42");
+}
+
+#[test]
+fn truncation_keeps_whole_rows() {
+    let text = "╭─\n╷ 1 2 3\n  4 5 6\n  7 8 9\n        ╯";
+    assert_eq!(truncate_output(text, 100), None);
+    assert_eq!(
+        truncate_output(text, 20).as_deref(),
+        Some("╭─\n╷ 1 2 3\n  4 5 6\n⋮")
+    );
+    assert_eq!(truncate_output("123456", 3).as_deref(), Some("123…"));
+}
+
+#[test]
+fn long_outputs_collapse_even_when_pretty() {
+    let settings = Settings::default();
+    assert!(settings.pretty_arrays);
+    // uiua summarizes much longer strings by itself
+    let long = "x".repeat(settings.max_output_chars * 3);
+    let output = ScrollbackOutput::from_uiuavalue(uiua::Value::from(long.as_str()), &settings);
+    let OutputMedia::Text(text) = &output.media else {
+        panic!("a string is shown as text");
+    };
+    assert!(matches!(
+        TextDisplay::of(text, settings.pretty_arrays, settings.max_output_chars),
+        TextDisplay::Collapsed(preview) if preview.chars().count() <= settings.max_output_chars + 1
+    ));
+    assert_eq!(TextDisplay::of("[1 2 3]", true, 100), TextDisplay::Pretty);
+    assert_eq!(TextDisplay::of("[1 2 3]", false, 100), TextDisplay::Plain);
+}
+
+#[test]
+fn onboarding_examples_show_off_every_kind() {
+    let kinds: Vec<_> = ONBOARDING_EXAMPLES
+        .iter()
+        .map(|(_, code)| run_uiua(code, &[], &Settings::default()).0.unwrap()[0].kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        [OutputKind::Image, OutputKind::Audio, OutputKind::Text]
+    );
+}
+
 #[test]
 fn keypad_has_all_prims() {
     fn prim_exists_in_keypad(p: P) -> bool {
@@ -267,7 +996,7 @@ fn keypad_has_all_prims() {
                 return true;
             }
         }
-        return false;
+        false
     }
     for prim in uiua::Primitive::non_deprecated() {
         if prim.glyph().is_none() || prim.is_experimental() {
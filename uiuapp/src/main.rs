@@ -1,11 +1,8 @@
 #![allow(non_snake_case)]
 
 use crate::document::*;
-use base64::engine::general_purpose;
-use base64::Engine;
 use dioxus::prelude::*;
 use dioxus_logger::tracing::{info, Level};
-use uiuapp::ScrollbackItem as SBI;
 use uiuapp::*;
 
 fn main() {
@@ -20,30 +17,62 @@ fn App() -> Element {
     static CSS: Asset = asset!("/assets/uiuapp.css");
     static _UIUA386: Asset = asset!("/assets/Uiua386.ttf");
 
-    // the text that's been input and evaluated
-    // populated for testing
-    // TODO(release): depopulate
-    let mut buffer_contents = use_signal(|| {
-        let code = "˙⊞=⇡3";
-        let output = SBI::Output(vec![run_uiua(code).unwrap()[0].clone()]);
-        let c = SBI::Input(highlight_code(code));
-
-        vec![
-            SBI::Input(highlight_code("+ 1 1")),
-            SBI::Output(vec![ScrollbackOutput::Text("2".to_string())]),
-            c.clone(),
-            output.clone(),
-            c.clone(),
-            output.clone(),
-            c.clone(),
-            output.clone(),
-        ]
+    let settings: Signal<Settings> = use_signal(Settings::load);
+    // Code shared through the url opens in a buffer of its own
+    let mut buffers: Signal<ScratchBuffers> = use_signal(|| {
+        ScratchBuffers::load_with_shared(share::take_code_from_location().as_deref())
     });
+    // the text that's been input and evaluated, in the active buffer
+    let mut buffer_contents: Signal<Vec<RunRecord>> =
+        use_signal(|| load_runs(buffers.peek().active, &settings.peek()));
     // Has been input but not yet evaluated
-    let mut input_contents = use_signal(String::new);
-    let touch_info: Signal<Option<LastTouchContext>> = use_signal(|| None);
+    let mut input_contents = use_signal(|| buffers.peek().active().input.clone());
+    let _touch_info: Signal<Option<LastTouchContext>> = use_signal(|| None);
     let rad_info: Signal<RadialInfo> = use_signal(RadialInfo::new);
-    let mut settings: Signal<Settings> = use_signal(Settings::default);
+    let mut history: Signal<InputHistory> = use_signal(InputHistory::load);
+    let panel: Signal<Option<Panel>> = use_signal(|| None);
+    // The input as it was when its link was last copied, so the share button can confirm it
+    let mut shared: Signal<Option<String>> = use_signal(|| None);
+    // Shown above the input until dismissed, for failures the user should know about
+    let mut app_error: Signal<Option<String>> = use_signal(|| None);
+
+    // Old outputs are re-encoded when the media settings change
+    let media_settings =
+        use_memo(move || (settings.read().media, settings.read().audio_sample_time));
+    // Starts out as what the restored scrollback was rendered with, so mounting doesn't redo it
+    let mut rendered_media = use_signal(|| *media_settings.peek());
+    use_effect(move || {
+        let current = media_settings();
+        if *rendered_media.peek() != current {
+            rendered_media.set(current);
+            rerender_scrollback(buffer_contents, &settings.peek());
+        }
+    });
+
+    // Old inputs are highlighted again when the formatter settings change
+    let format_settings = use_memo(move || settings.read().format);
+    let mut highlighted_format = use_signal(|| *format_settings.peek());
+    use_effect(move || {
+        let current = format_settings();
+        if *highlighted_format.peek() != current {
+            highlighted_format.set(current);
+            rehighlight_scrollback(buffer_contents, &settings.peek());
+        }
+    });
+
+    use_effect(move || settings.read().save());
+
+    // The active buffer keeps its draft and scrollback across reloads
+    use_effect(move || {
+        let input = input_contents();
+        buffers.write().active_mut().input = input;
+        buffers.peek().save();
+    });
+    use_effect(move || {
+        if let Err(e) = save_runs(buffers.peek().active, &buffer_contents.read()) {
+            app_error.set(Some(e));
+        }
+    });
 
     rsx! {
         Meta { charset: "UTF-8" }
@@ -54,120 +83,110 @@ fn App() -> Element {
         Title { "cas/uiuapp" }
         Stylesheet { href: CSS }
 
-        div { class: "app",
+        div { class: "app {settings.read().gayness.class()}", "data-theme": settings.read().theme.resolved().attr(),
             div { class: "top-bar",
                 button {
-                    onclick: move |e| {
-                        info!("Settings button pressed (unimplemented as of yet)");
-                        // Settings button toggles this specific one, for testing
-                        // TODO: implement an actual settings menu
-                        let b = settings.read().clean_input_on_run;
-                        settings.write().clean_input_on_run = !b;
-                    },
+                    onclick: move |_| toggle_panel(panel, Panel::Settings),
                     "Settings"
                 }
+                button {
+                    onclick: move |_| toggle_panel(panel, Panel::History),
+                    "History"
+                }
+                button {
+                    onclick: move |_| toggle_panel(panel, Panel::Examples),
+                    "Examples"
+                }
+                button {
+                    onclick: move |_| toggle_panel(panel, Panel::Reference),
+                    "Reference"
+                }
+                button {
+                    onclick: move |_| toggle_panel(panel, Panel::Files),
+                    "Files"
+                }
+                button {
+                    onclick: move |_| share::open_in_pad(&input_contents.read()),
+                    "Pad"
+                }
+                button {
+                    onclick: move |_| {
+                        copy_to_clipboard(&share::share_url(&input_contents.read()));
+                        shared.set(Some(input_contents()));
+                    },
+                    if shared() == Some(input_contents()) { "Copied!" } else { "Share" }
+                }
+            }
+            BufferTabs { buffers, input_contents, buffer_contents, settings }
+            match panel() {
+                Some(Panel::Settings) => rsx! { SettingsPanel { settings } },
+                Some(Panel::History) => rsx! { HistoryPanel { input_contents, history, panel } },
+                Some(Panel::Reference) => rsx! {
+                    ReferencePanel { buffer_contents, history, buffers, settings }
+                },
+                Some(Panel::Examples) => rsx! {
+                    ExamplesPanel { input_contents, buffer_contents, history, buffers, settings, panel }
+                },
+                Some(Panel::Files) => rsx! {
+                    FilesPanel { input_contents, buffer_contents, history, buffers, settings, panel }
+                },
+                None => rsx! {},
             }
             div { class: "code-view-zone",
-                for item in buffer_contents.read().clone() {
-                    {
-                        match item {
-                            SBI::Input(input) => {
-                                rsx! {
-                                    p { class: "user-input",
-                                    onclick: move |_e| {
-                                        if input_contents().is_empty() {
-                                            *input_contents.write() = match input {
-                                                Ok(ref v) => v.iter().map(|uhs| match uhs {
-                                                    // Bunch of cloning, this should be benchmarked
-                                                    UiuappHistorySpan::UnstyledCode { text } => text.clone(),
-                                                    UiuappHistorySpan::StyledCode { text, .. } => text.clone(),
-                                                    UiuappHistorySpan::Whitspace(text) => text.clone(),
-                                                }).collect::<Vec<String>>().join(""),
-                                                Err(ref s) => s.to_string()
-                                            };
-                                        }
-                                    },
-                                        match input {
-                                            Ok(ref v) => {
-                                                rsx! {
-                                                    for uhs in v {
-                                                        match uhs {
-                                                            UiuappHistorySpan::UnstyledCode { text } => rsx! { span { "{text}" } },
-                                                            UiuappHistorySpan::StyledCode { class: c, text } => rsx! { span { class: "{c}", "{text}"} },
-                                                            UiuappHistorySpan::Whitspace(text) => rsx! { span { "{text}" } },
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            Err(ref s) => rsx! { span { "{s}" } }
-                                        }
-
-                                    }
-                                }
-                            },
-                            SBI::Output(outputs) => {
-                                let outputs = match settings.read().stack_ordering {
-                                    StackOrdering::TopAtTop => outputs,
-                                    StackOrdering::BottomAtTop => outputs.into_iter().rev().collect(),
-                                };
-                                rsx! {
-                                    for output in outputs {
-                                        match output {
-                                            ScrollbackOutput::Text(text) => {
-                                                info!("TEXT");
-                                                rsx! {
-                                                    p { class: "user-result", "{text}" }
-                                                }
-                                            },
-                                            ScrollbackOutput::Image(bytes) => {
-                                                let data = general_purpose::STANDARD.encode(&bytes);
-                                                rsx! {
-                                                    img { class: "user-result", src: "data:image/png;base64,{data}" }
-                                                }
-                                            },
-                                            ScrollbackOutput::Audio(bytes) => {
-                                                let data = general_purpose::STANDARD.encode(&bytes);
-                                                rsx! {
-                                                    audio { class: "user-result", controls: true, src: "data:audio/wav;base64,{data}" }
-                                                }
-                                            },
-                                            ScrollbackOutput::Gif(bytes) => {
-                                                let data = general_purpose::STANDARD.encode(&bytes);
-                                                rsx! {
-                                                    img { class: "user-result", src: "data:image/gif;base64,{data}" }
-                                                }
-                                            },
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if history.read().entries.is_empty() && buffer_contents.read().is_empty() {
+                    Welcome { buffer_contents, history, buffers, settings }
+                }
+                for run in buffer_contents.read().clone() {
+                    RunEntry { key: "{run.id}", run, buffer_contents, input_contents, buffers, settings, app_error }
                 }
             }
+              if let Some(error) = app_error() {
+                  div { class: "app-error", onclick: move |_| app_error.set(None), "{error}" }
+              }
               div { class: "input-zone",
                     RadialSelector { input_contents, rad_info }
                     div { class: "input-bar",
                     // This textarea should bring up the native keyboard for
                     // ascii-and-related typing
-                          textarea { class: "text-box", rows: 2,
+                          textarea { class: "text-box", id: INPUT_ID, rows: 2,
                                      onkeydown: move |e| {
-                                         if let Key::Enter = e.key() {
-                                             info!("Return gotten");
-                                             if e.modifiers().contains(Modifiers::CONTROL) {
+                                         let ctrl = e.modifiers().contains(Modifiers::CONTROL);
+                                         match e.key() {
+                                             Key::Enter if ctrl => {
                                                  e.prevent_default();
                                                  info!("Running from shortcut");
-                                                 handle_running_code(input_contents, buffer_contents, settings);
+                                                 handle_running_code(input_contents, buffer_contents, history, buffers, settings);
+                                             }
+                                             Key::ArrowUp if ctrl => {
+                                                 e.prevent_default();
+                                                 let prev = history.write().older(&input_contents.read());
+                                                 if let Some(code) = prev { input_contents.set(code); }
                                              }
+                                             Key::ArrowDown if ctrl => {
+                                                 e.prevent_default();
+                                                 let next = history.write().newer();
+                                                 if let Some(code) = next { input_contents.set(code); }
+                                             }
+                                             _ => {}
                                          }
                                      },
-                                     onchange: move |e| {
-                                         *input_contents.write() = e.value();
+                                     oninput: move |e| {
+                                         let mut value = e.value();
+                                         if settings.read().live_glyphs {
+                                             let glyphified = caret_position(INPUT_ID).and_then(|caret| {
+                                                 glyphify_at_caret(&value, caret, &settings.read().format)
+                                             });
+                                             if let Some((text, caret)) = glyphified {
+                                                 set_text_and_caret(INPUT_ID, &text, caret);
+                                                 value = text;
+                                             }
+                                         }
+                                         *input_contents.write() = value;
                                      },
                                      value: input_contents }
                           button { class: "run-button",
-                                   onclick: move |e| {
-                                       handle_running_code(input_contents, buffer_contents, settings);
+                                   onclick: move |_| {
+                                       handle_running_code(input_contents, buffer_contents, history, buffers, settings);
                                    },
                                    "Run" },
                     }
@@ -176,11 +195,23 @@ fn App() -> Element {
                           button { class: "special-button", onclick: move |_| {*buffer_contents.write() = vec![];}, "Clear Past" }
                           button { class: "special-button", onclick: move |_| {*input_contents.write() = "".to_string();}, "Clear Curr" }
                           button { class: "special-button", onclick: move |_| {input_contents.write().push(';');}, ";" }
-                          // TODO: Decide if these arrows should even exist
-                          /*button { class: "special-button", "←" } // TODO: position cursor
-                          button { class: "special-button", "↓" }
-                          button { class: "special-button", "↑" }
-                          button { class: "special-button", "→" }*/
+                          // Fills in the `##` output comments in place
+                          button { class: "special-button", onclick: move |_| {
+                              let filled = evaluate_output_comments(&input_contents.read(), &buffers.read().prelude(), &settings.read().format);
+                              match filled {
+                                  Ok(code) => input_contents.set(code),
+                                  Err(e) => app_error.set(Some(format!("Could not evaluate output comments: {e}"))),
+                              }
+                          }, "##" }
+                          // History navigation, like a shell's
+                          button { class: "special-button", onclick: move |_| {
+                              let prev = history.write().older(&input_contents.read());
+                              if let Some(code) = prev { input_contents.set(code); }
+                          }, "↑" }
+                          button { class: "special-button", onclick: move |_| {
+                              let next = history.write().newer();
+                              if let Some(code) = next { input_contents.set(code); }
+                          }, "↓" }
                           button { class: "special-button", onclick: move |_| {input_contents.write().pop();}, "Bksp" }
                     }
                     div { class: "input-grid-buttons",
@@ -1,47 +1,125 @@
 use crate::*;
 use dioxus_logger::tracing::*;
 
-use ScrollbackOutput as O;
-const MIN_AUTO_IMAGE_DIM: usize = 30;
-const AUDIO_SAMPLE_RATE: u32 = 44100; // TODO: This should be in the Settings menu
+use OutputMedia as O;
 
 impl ScrollbackOutput {
-    pub fn from_uiuavalue(value: uiua::Value, settings: ()) -> Self {
-        use uiua::media::*;
-        use uiua::Value;
-
-        // Gif
-        if let Ok(gif) = value_to_gif_bytes(&value, 16.0) {
-            match &*value.shape {
-                &[f, h, w] | &[f, h, w, _]
-                    if h >= MIN_AUTO_IMAGE_DIM && w >= MIN_AUTO_IMAGE_DIM && f >= 5 =>
-                {
-                    trace!("Turning gif into bytes");
-                    return O::Gif(gif);
+    /// Guesses how `value` is best shown, falling back to text
+    pub fn from_uiuavalue(value: uiua::Value, settings: &Settings) -> Self {
+        Self {
+            media: detect_media(&value, settings),
+            meta: ValueMeta::of(&value),
+            value,
+            kind_override: None,
+        }
+    }
+
+    /// Shows the value as `kind`, skipping the heuristics. Fails if the value can't be
+    /// encoded that way (e.g. a list of boxes as an image), in which case nothing changes
+    pub fn set_kind(&mut self, kind: OutputKind, settings: &Settings) -> Result<(), String> {
+        self.media = encode_media(&self.value, kind, settings)?;
+        self.kind_override = Some(kind);
+        Ok(())
+    }
+
+    /// Encodes the value again with the current settings
+    pub fn rerender(&mut self, settings: &Settings) {
+        if let Some(kind) = self.kind_override {
+            match encode_media(&self.value, kind, settings) {
+                Ok(media) => {
+                    self.media = media;
+                    return;
                 }
-                _ => {}
+                Err(e) => warn!("Cannot show output as {} anymore: {e}", kind.name()),
             }
         }
-        // Image?
-        if let Ok(image) = value_to_image(&value) {
-            if image.width() >= MIN_AUTO_IMAGE_DIM as u32
-                && image.height() >= MIN_AUTO_IMAGE_DIM as u32
+        self.media = detect_media(&self.value, settings);
+        self.kind_override = None;
+    }
+
+    pub fn kind(&self) -> OutputKind {
+        match self.media {
+            O::Text(_) => OutputKind::Text,
+            O::Image(_) => OutputKind::Image,
+            O::Gif(_) => OutputKind::Gif,
+            O::Audio(_) => OutputKind::Audio,
+        }
+    }
+}
+
+impl OutputMedia {
+    /// The mime type and file extension of media outputs
+    pub fn mime(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            O::Text(_) => None,
+            O::Image(_) => Some(("image/png", "png")),
+            O::Gif(_) => Some(("image/gif", "gif")),
+            O::Audio(_) => Some(("audio/wav", "wav")),
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            O::Text(text) => text.as_bytes(),
+            O::Image(bytes) | O::Gif(bytes) | O::Audio(bytes) => bytes,
+        }
+    }
+}
+
+fn detect_media(value: &uiua::Value, settings: &Settings) -> OutputMedia {
+    use uiua::media::*;
+    use uiua::Value;
+    let media = &settings.media;
+
+    // Gif
+    if let Ok(gif) = value_to_gif_bytes(value, media.gif_fps) {
+        match &*value.shape {
+            &[f, h, w] | &[f, h, w, _]
+                if h >= media.min_image_dim
+                    && w >= media.min_image_dim
+                    && f >= media.gif_min_frames =>
             {
-                if let Ok(bytes) = image_to_bytes(&image, image::ImageFormat::Png) {
-                    trace!("Turning image into bytes");
-                    return O::Image(bytes);
-                }
+                trace!("Turning gif into bytes");
+                return O::Gif(gif);
             }
+            _ => {}
         }
-        // Audio?
-        if value.row_count() as u32 >= AUDIO_SAMPLE_RATE / 4
-            && matches!(&value, Value::Num(arr) if arr.elements().all(|x| x.abs() <= 5.0))
+    }
+    // Image?
+    if let Ok(image) = value_to_image(value) {
+        if image.width() >= media.min_image_dim as u32
+            && image.height() >= media.min_image_dim as u32
         {
-            if let Ok(bytes) = value_to_wav_bytes(&value, AUDIO_SAMPLE_RATE) {
-                return Self::Audio(bytes);
+            if let Ok(bytes) = image_to_bytes(&image, image::ImageFormat::Png) {
+                trace!("Turning image into bytes");
+                return O::Image(bytes);
             }
         }
-
-        O::Text(value.show())
     }
+    // Audio?
+    if media.detect_audio
+        && value.row_count() as u32 >= settings.audio_sample_time / 4
+        && matches!(value, Value::Num(arr) if arr.elements().all(|x| x.abs() <= 5.0))
+    {
+        if let Ok(bytes) = value_to_wav_bytes(value, settings.audio_sample_time) {
+            return O::Audio(bytes);
+        }
+    }
+
+    O::Text(value.show())
+}
+
+fn encode_media(
+    value: &uiua::Value,
+    kind: OutputKind,
+    settings: &Settings,
+) -> Result<OutputMedia, String> {
+    use uiua::media::*;
+
+    Ok(match kind {
+        OutputKind::Text => O::Text(value.show()),
+        OutputKind::Image => O::Image(value_to_image_bytes(value, image::ImageFormat::Png)?),
+        OutputKind::Audio => O::Audio(value_to_wav_bytes(value, settings.audio_sample_time)?),
+        OutputKind::Gif => O::Gif(value_to_gif_bytes(value, settings.media.gif_fps)?),
+    })
 }
@@ -0,0 +1,171 @@
+//! A browsable reference of every primitive, with the docs the uiua crate ships
+use crate::*;
+use dioxus::prelude::*;
+use uiua::{PrimDocFragment, PrimDocLine};
+
+/// Every non-deprecated primitive, grouped by class
+pub fn primitives_by_class() -> Vec<(String, Vec<P>)> {
+    PrimClass::all()
+        .map(|class| {
+            let prims: Vec<P> = class.primitives().filter(|p| !p.is_deprecated()).collect();
+            (class_name(class), prims)
+        })
+        .filter(|(_, prims)| !prims.is_empty())
+        .collect()
+}
+
+/// A human name for a class, e.g. "Monadic pervasive" for `MonadicPervasive`
+pub fn class_name(class: PrimClass) -> String {
+    let debug = match class {
+        PrimClass::Sys(sys) => return format!("System: {sys:?}"),
+        class => format!("{class:?}"),
+    };
+    let mut name = String::new();
+    for (i, c) in debug.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            name.push(' ');
+            name.extend(c.to_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// The signature of a function (`|2.1`), or how many functions a modifier takes
+pub fn signature_of(p: &P) -> Option<String> {
+    if let Some(args) = p.modifier_args() {
+        Some(format!(
+            "{args} function{}",
+            if args == 1 { "" } else { "s" }
+        ))
+    } else {
+        p.sig().map(|sig| sig.to_string())
+    }
+}
+
+/// Whether `p` is what's being searched for, by glyph, name or ascii spelling
+pub fn matches_query(p: &P, query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    query.is_empty()
+        || p.name().contains(&query)
+        || p.glyph().is_some_and(|g| query.contains(g))
+        || p.ascii().is_some_and(|a| a.to_string() == query)
+}
+
+#[component]
+pub fn ReferencePanel(
+    buffer_contents: Signal<Vec<RunRecord>>,
+    history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    rsx! {
+        div { class: "reference-panel",
+            input { class: "history-search", placeholder: "Search by name or glyph",
+                    value: query,
+                    oninput: move |e| query.set(e.value()) }
+            for (class, prims) in primitives_by_class() {
+                {
+                    let found: Vec<P> = prims.into_iter().filter(|p| matches_query(p, &query())).collect();
+                    rsx! {
+                        if !found.is_empty() {
+                            h3 { "{class}" }
+                            for p in found {
+                                PrimitiveEntry { key: "{p.name()}", prim: p, buffer_contents, history, buffers, settings }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A primitive's summary, which expands into its full documentation when tapped
+#[component]
+fn PrimitiveEntry(
+    prim: P,
+    buffer_contents: Signal<Vec<RunRecord>>,
+    history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let doc = prim.doc();
+    rsx! {
+        div { class: "primitive-entry",
+            div { class: "primitive-summary",
+                  onclick: move |_| {
+                      let o = open();
+                      open.set(!o);
+                  },
+                  span { class: "primitive-glyph {css_of_prim(&prim)}", "{prim.glyph().unwrap_or(' ')}" }
+                  span { class: "primitive-name", "{prim.name()}" }
+                  if prim.is_experimental() {
+                      span { "{EXPERIMENTAL_ICON}" }
+                  }
+                  if let Some(sig) = signature_of(&prim) {
+                      span { class: "primitive-signature", "{sig}" }
+                  }
+                  p { class: "primitive-short", "{doc.short_text()}" }
+            }
+            if open() {
+                for line in doc.lines.iter() {
+                    match line {
+                        PrimDocLine::Text(fragments) => rsx! {
+                            p { class: "primitive-doc", {doc_fragments(fragments)} }
+                        },
+                        PrimDocLine::Example(example) => {
+                            let code = example.input().to_string();
+                            rsx! {
+                                div { class: "primitive-example",
+                                      HighlightedCode { code: code.clone(), format: settings.read().format, rainbow: settings.read().rainbow_brackets }
+                                      button { onclick: move |_| {
+                                                   run_into_scrollback(&code, buffer_contents, history, buffers, settings);
+                                               },
+                                               "Run" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn doc_fragments(fragments: &[PrimDocFragment]) -> Element {
+    rsx! {
+        for fragment in fragments.iter().cloned() {
+            match fragment {
+                PrimDocFragment::Text(t) => rsx! { "{t}" },
+                PrimDocFragment::Code(c) => rsx! { code { "{c}" } },
+                PrimDocFragment::Emphasis(e) => rsx! { em { "{e}" } },
+                PrimDocFragment::Strong(s) => rsx! { strong { "{s}" } },
+                PrimDocFragment::Primitive { prim, named } => rsx! {
+                    span { class: css_of_prim(&prim),
+                        "{prim.glyph().map(String::from).unwrap_or_default()}"
+                        if named || prim.glyph().is_none() {
+                            " {prim.name()}"
+                        }
+                    }
+                },
+                PrimDocFragment::Link { text, url } => rsx! {
+                    a { href: "{url}", target: "_blank", "{text}" }
+                },
+            }
+        }
+    }
+}
+
+#[test]
+fn reference_covers_every_primitive() {
+    let listed: usize = primitives_by_class().iter().map(|(_, ps)| ps.len()).sum();
+    assert_eq!(listed, P::non_deprecated().count());
+    assert_eq!(class_name(PrimClass::MonadicPervasive), "Monadic pervasive");
+    assert!(matches_query(&P::Reverse, "rev"));
+    assert!(matches_query(&P::Reverse, "⇌"));
+    assert!(!matches_query(&P::Reverse, "range"));
+}
@@ -0,0 +1,156 @@
+//! Sessions as `.ua` files: inputs as code, outputs as `##` output comments
+use crate::{RunRecord, Settings};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// The whole scrollback as a uiua file, each input followed by what it output
+pub fn export(runs: &[RunRecord]) -> String {
+    let mut file = String::new();
+    for run in runs {
+        file.push_str(run.code.trim_end());
+        file.push('\n');
+        let shown = run.outputs.iter().map(|o| o.value.show());
+        for text in shown.chain(run.error.clone()) {
+            for line in text.lines() {
+                file.push_str("## ");
+                file.push_str(line);
+                file.push('\n');
+            }
+        }
+    }
+    file
+}
+
+/// A `data:` url of the exported session, for a download link
+pub fn export_url(runs: &[RunRecord]) -> String {
+    format!(
+        "data:text/plain;charset=utf-8;base64,{}",
+        STANDARD.encode(export(runs))
+    )
+}
+
+/// What to run of a file, one top-level line at a time
+///
+/// Indented lines, lines inside unclosed brackets and the lines of a multi-line `$ ` raw string
+/// are kept with the line before them, so multi-line code stays whole.
+/// Output comments are skipped, as running regenerates them.
+pub fn runnable_lines(file: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0i32;
+    let mut in_raw_string = false;
+    for line in file.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("##") {
+            continue;
+        }
+        let continues = depth > 0
+            || line.starts_with(char::is_whitespace)
+            || (in_raw_string && trimmed.starts_with('$'));
+        let scanned = scan_line(line);
+        depth += scanned.depth;
+        in_raw_string = scanned.raw_string;
+        match lines.last_mut() {
+            Some(prev) if continues => {
+                prev.push('\n');
+                prev.push_str(line);
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Runs a file one of its [runnable_lines] at a time, after the code in `prelude`
+///
+/// Lines that leave nothing on the stack, like bindings and imports, are loaded before the
+/// lines after them, so that later lines can use what earlier ones defined.
+pub fn run_lines(file: &str, prelude: &[String], settings: &Settings) -> Vec<RunRecord> {
+    let mut prelude = prelude.to_vec();
+    let mut runs = Vec::new();
+    for line in runnable_lines(file) {
+        let run = RunRecord::run(&line, &prelude, settings);
+        if run.error.is_none() && run.outputs.is_empty() {
+            prelude.push(line);
+        }
+        runs.push(run);
+    }
+    runs
+}
+
+/// What a line leaves open for the lines after it
+struct LineScan {
+    /// How many more brackets it opens than it closes, outside of strings, characters and comments
+    depth: i32,
+    /// Whether it ends in a `$ ` raw string, which the next line may carry on
+    raw_string: bool,
+}
+
+fn scan_line(line: &str) -> LineScan {
+    let mut scan = LineScan {
+        depth: 0,
+        raw_string: false,
+    };
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            // A character literal, which may be an escape like `@\"`
+            '@' if !in_string => {
+                let escaped = chars.next() == Some('\\');
+                if escaped {
+                    chars.next();
+                }
+            }
+            '$' if !in_string && matches!(chars.peek(), None | Some(' ')) => {
+                scan.raw_string = true;
+                break;
+            }
+            '#' if !in_string => break,
+            '(' | '[' | '{' if !in_string => scan.depth += 1,
+            ')' | ']' | '}' if !in_string => scan.depth -= 1,
+            _ => {}
+        }
+    }
+    scan
+}
+
+#[test]
+fn exported_sessions_run_the_same_when_imported() {
+    let settings = Settings::default();
+    let codes = [
+        "F ← (\n  +1\n)",
+        "+1 1",
+        "⇡3",
+        "$ a (\n$ b",
+        "⊂@( @\"",
+        "⊂@\\\" \"$\"",
+        "$\"_\" 1",
+    ];
+    let runs: Vec<RunRecord> = codes
+        .iter()
+        .map(|code| RunRecord::run(code, &[], &settings))
+        .collect();
+    assert!(runs.iter().all(|run| run.error.is_none()));
+    let file = export(&runs);
+    assert!(file.contains("## 2\n"));
+    assert!(file.contains("## [0 1 2]\n"));
+    assert_eq!(runnable_lines(&file), codes);
+}
+
+#[test]
+fn imported_lines_see_earlier_definitions() {
+    let file = "F ← +1\nF 2\n## 3\nG ← ×2\nG F 2";
+    let runs = run_lines(file, &[], &Settings::default());
+    let codes: Vec<&str> = runs.iter().map(|run| run.code.as_str()).collect();
+    assert_eq!(codes, ["F ← +1", "F 2", "G ← ×2", "G F 2"]);
+    assert!(runs.iter().all(|run| run.error.is_none()));
+    let shown: Vec<Vec<String>> = runs
+        .iter()
+        .map(|run| run.outputs.iter().map(|o| o.value.show()).collect())
+        .collect();
+    assert_eq!(shown, [vec![], vec!["3"], vec![], vec!["6"]]);
+}
@@ -0,0 +1,128 @@
+//! Links that carry a program, in the same format as the official pad's
+//! (`?src=<version>__<url-safe base64 of the code>`)
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, URL_SAFE};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use dioxus_logger::tracing::*;
+use web_sys::wasm_bindgen::JsValue;
+
+pub const PAD_URL: &str = "https://uiua.org/pad";
+
+/// Older links don't always come padded, so padding is optional when decoding
+const LENIENT_URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// The value of a `src` parameter for `code`
+pub fn encode_src(code: &str) -> String {
+    format!(
+        "{}__{}",
+        uiua::VERSION.replace('.', "_"),
+        URL_SAFE.encode(code)
+    )
+}
+
+/// The code in a `src` parameter. The version prefix is optional, as it is in the pad
+pub fn decode_src(src: &str) -> Option<String> {
+    let encoded = src
+        .split_once("__")
+        .map_or(src, |(_version, encoded)| encoded);
+    let encoded = encoded.replace("%3D", "=").replace("%3d", "=");
+    let bytes = LENIENT_URL_SAFE.decode(encoded).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Finds the code in a url's fragment or query, e.g. `#src=...` or `?src=...`
+pub fn code_from_url(url: &str) -> Option<String> {
+    url.split(['?', '#', '&'])
+        .find_map(|param| param.strip_prefix("src="))
+        .and_then(decode_src)
+}
+
+/// A link that opens `code` in this app, relative to wherever it's being served from
+pub fn share_url(code: &str) -> String {
+    let base = web_sys::window()
+        .and_then(|w| w.location().href().ok())
+        .unwrap_or_default();
+    let base = base.split('#').next().unwrap_or_default();
+    format!("{base}#src={}", encode_src(code))
+}
+
+/// A link that opens `code` in the official pad
+pub fn pad_url(code: &str) -> String {
+    format!("{PAD_URL}?src={}", encode_src(code))
+}
+
+/// Opens `code` in the official pad, in a new tab
+pub fn open_in_pad(code: &str) {
+    let url = pad_url(code);
+    let opened = web_sys::window().map(|w| w.open_with_url_and_target(&url, "_blank"));
+    if !matches!(opened, Some(Ok(Some(_)))) {
+        warn!("Could not open {url}");
+    }
+}
+
+/// `url` without any `src` parameter, in its query or its fragment
+pub fn url_without_src(url: &str) -> String {
+    let keep = |params: &str| {
+        let kept: Vec<&str> = params
+            .split('&')
+            .filter(|p| !p.is_empty() && !p.starts_with("src="))
+            .collect();
+        kept.join("&")
+    };
+    let (rest, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let (base, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut url = base.to_string();
+    for (sep, params) in [('?', keep(query)), ('#', keep(fragment))] {
+        if !params.is_empty() {
+            url.push(sep);
+            url.push_str(&params);
+        }
+    }
+    url
+}
+
+/// The code in the url the app was opened with, if any. It's taken out of the url,
+/// so that reloading doesn't open it again
+pub fn take_code_from_location() -> Option<String> {
+    let window = web_sys::window()?;
+    let href = window.location().href().ok()?;
+    let code = code_from_url(&href);
+    if code.is_none() && href.contains("src=") {
+        warn!("Could not decode the code in {href}");
+    }
+    if href.contains("src=") {
+        let cleaned = url_without_src(&href);
+        let replaced = window
+            .history()
+            .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some(&cleaned)));
+        if replaced.is_err() {
+            warn!("Could not take the code out of {href}");
+        }
+    }
+    code
+}
+
+#[test]
+fn share_links_round_trip() {
+    let code = "⇌⇡10 # Reverse\n\"ünïcode\"";
+    let src = encode_src(code);
+    assert!(src.starts_with(&uiua::VERSION.replace('.', "_")));
+    assert_eq!(decode_src(&src).as_deref(), Some(code));
+    assert_eq!(
+        code_from_url(&format!("https://example.com/uiuapp/#src={src}")).as_deref(),
+        Some(code)
+    );
+    assert_eq!(code_from_url(&pad_url(code)).as_deref(), Some(code));
+    // Unversioned and unpadded
+    assert_eq!(decode_src("KyAxIDE").as_deref(), Some("+ 1 1"));
+    assert_eq!(code_from_url("https://uiua.org/pad"), None);
+    assert_eq!(
+        url_without_src(&format!("https://example.com/uiuapp/?a=1#src={src}")),
+        "https://example.com/uiuapp/?a=1"
+    );
+    assert_eq!(url_without_src(&pad_url(code)), PAD_URL);
+}
@@ -0,0 +1,45 @@
+//! Persistence through the browser's `localStorage`
+use dioxus_logger::tracing::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Loads whatever was last saved under `key`, if anything (and if it still parses)
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = local_storage()?.get_item(key).ok()??;
+    match serde_json::from_str(&json) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!("Discarding stored '{key}': {e}");
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if let Err(e) = try_save(key, value) {
+        warn!("{e}");
+    }
+}
+
+/// Like [save], but hands back what went wrong so it can be shown
+pub fn try_save<T: Serialize>(key: &str, value: &T) -> Result<(), String> {
+    let storage =
+        local_storage().ok_or_else(|| format!("No local storage, '{key}' won't be persisted"))?;
+    let json =
+        serde_json::to_string(value).map_err(|e| format!("Could not serialize '{key}': {e}"))?;
+    storage
+        .set_item(key, &json)
+        .map_err(|_| format!("Could not persist '{key}' (storage full?)"))
+}
+
+/// Forgets whatever was saved under `key`
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        if storage.remove_item(key).is_err() {
+            warn!("Could not remove '{key}'");
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long each half of a run took
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct RunTimings {
+    pub compile: Duration,
+    pub execution: Duration,
+}
+
+impl RunTimings {
+    pub fn total(&self) -> Duration {
+        self.compile + self.execution
+    }
+}
+
+/// The spread of total run times when benchmarking
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkStats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl BenchmarkStats {
+    pub fn of(mut times: Vec<Duration>) -> Option<Self> {
+        times.sort();
+        Some(Self {
+            runs: times.len(),
+            min: *times.first()?,
+            median: times[times.len() / 2],
+            max: *times.last()?,
+        })
+    }
+}
+
+/// Milliseconds since the unix epoch
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.)
+            .unwrap_or_default()
+    }
+}
+
+/// A high resolution timestamp in milliseconds, only meaningful relative to another one
+pub fn performance_now() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(performance) = web_sys::window().and_then(|w| w.performance()) {
+        return performance.now();
+    }
+    now_ms()
+}
+
+/// Time elapsed since `start`, as given by [performance_now]
+pub fn elapsed_since(start: f64) -> Duration {
+    Duration::from_secs_f64((performance_now() - start).max(0.) / 1000.)
+}
+
+pub fn format_duration(d: Duration) -> String {
+    if d < Duration::from_millis(1) {
+        format!("{}µs", d.as_micros())
+    } else if d < Duration::from_secs(1) {
+        format!("{:.1}ms", d.as_secs_f64() * 1000.)
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
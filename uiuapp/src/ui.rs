@@ -1,5 +1,8 @@
 use crate::*;
+use base64::engine::general_purpose;
+use base64::Engine;
 use dioxus::prelude::*;
+use dioxus_logger::tracing::warn;
 
 #[component]
 pub fn RadialSelector(input_contents: Signal<String>, rad_info: Signal<RadialInfo>) -> Element {
@@ -22,7 +25,7 @@ pub fn RadialSelector(input_contents: Signal<String>, rad_info: Signal<RadialInf
                                                input_contents.write().push_str(&primes.iter().map(|p|p.glyph().unwrap_or(UNKNOWN_GLYPH)).collect::<String>());
                                            },
                                            for p in prims {
-                                               span { class: css_of_prim(&p), "{p.glyph().unwrap_or(UNKNOWN_GLYPH)}" }
+                                               span { class: css_of_prim(p), "{p.glyph().unwrap_or(UNKNOWN_GLYPH)}" }
                                            }
                                   }
                               }
@@ -34,7 +37,7 @@ pub fn RadialSelector(input_contents: Signal<String>, rad_info: Signal<RadialInf
                                   button {
                                       onclick: move |e| {
                                           e.prevent_default();
-                                          if &s != &EXPERIMENTAL_ICON {
+                                          if s != EXPERIMENTAL_ICON {
                                               input_contents.write().push_str(s);
                                           }
                                       },
@@ -76,7 +79,7 @@ pub fn ButtonIcons(input_contents: Signal<String>, rad_info: Signal<RadialInfo>)
                                      input_contents.write().push_str(&primsP.iter().map(|p|p.glyph().unwrap_or(UNKNOWN_GLYPH)).collect::<String>());
                                  },
                                 for p in prims {
-                                    span { class: css_of_prim(&p), "{p.glyph().unwrap_or(UNKNOWN_GLYPH)}" }
+                                    span { class: css_of_prim(p), "{p.glyph().unwrap_or(UNKNOWN_GLYPH)}" }
                                 }
                         }
                     }
@@ -100,6 +103,674 @@ pub fn ButtonIcons(input_contents: Signal<String>, rad_info: Signal<RadialInfo>)
     }
 }
 
+/// A single stack value in the scrollback, along with the buttons to show it as something else
+#[component]
+pub fn ScrollbackOutputView(
+    output: ScrollbackOutput,
+    pretty: bool,
+    max_chars: usize,
+    ordering: StackOrdering,
+    onrekind: EventHandler<OutputKind>,
+) -> Element {
+    let current = output.kind();
+    let mut expanded = use_signal(|| false);
+    let meta = &output.meta;
+    let data_url = match output.media.mime() {
+        Some((mime, _)) => format!(
+            "data:{mime};base64,{}",
+            general_purpose::STANDARD.encode(output.media.bytes())
+        ),
+        None => String::new(),
+    };
+    rsx! {
+        div { class: "user-output",
+            p { class: "output-caption",
+                span { class: "output-position", "{meta.position(&ordering)}" }
+                " {meta.shape} {meta.type_name}"
+                if let Some(label) = &meta.label {
+                    span { class: "output-label", " ${label}" }
+                }
+            }
+            match &output.media {
+                OutputMedia::Text(text) => match TextDisplay::of(text, pretty, max_chars) {
+                    TextDisplay::Pretty => rsx! {
+                        div { class: "user-result",
+                              ArrayView { value: output.value.clone() }
+                        }
+                    },
+                    TextDisplay::Collapsed(preview) if !expanded() => rsx! {
+                        p { class: "user-result truncated",
+                            onclick: move |_| expanded.set(true),
+                            "{preview}"
+                        }
+                        p { class: "truncation-note",
+                            "{output.value.shape} {output.value.type_name_plural()}, tap to expand"
+                        }
+                    },
+                    TextDisplay::Collapsed(_) => rsx! {
+                        p { class: "user-result",
+                            onclick: move |_| expanded.set(false),
+                            "{text}"
+                        }
+                    },
+                    TextDisplay::Plain => rsx! {
+                        p { class: "user-result", "{text}" }
+                    },
+                },
+                OutputMedia::Image(_) | OutputMedia::Gif(_) => rsx! {
+                    img { class: "user-result", src: "{data_url}" }
+                },
+                OutputMedia::Audio(_) => rsx! {
+                    audio { class: "user-result", controls: true, src: "{data_url}" }
+                },
+            }
+            div { class: "output-kinds",
+                button { class: "output-kind",
+                         onclick: {
+                             let text = output.value.show();
+                             move |_| copy_to_clipboard(&text)
+                         },
+                         "copy" }
+                if let Some((_, extension)) = output.media.mime() {
+                    a { class: "output-kind",
+                        href: "{data_url}",
+                        download: "uiuapp.{extension}",
+                        "save" }
+                }
+                for kind in OutputKind::ALL {
+                    button {
+                        class: if kind == current { "output-kind selected" } else { "output-kind" },
+                        onclick: move |_| onrekind.call(kind),
+                        "{kind.name()}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What can be done to a past input (and its result) from its action menu
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryAction {
+    Rerun,
+    /// Replace the code with this and run it again
+    Edit(String),
+    Delete,
+}
+
+/// A past input, which can be tapped to recall it or acted upon from its menu
+#[component]
+pub fn InputEntry(
+    code: String,
+    spans: Vec<UiuappHistorySpan>,
+    rainbow: bool,
+    input_contents: Signal<String>,
+    onaction: EventHandler<EntryAction>,
+) -> Element {
+    let mut menu_open = use_signal(|| false);
+    let mut editing: Signal<Option<String>> = use_signal(|| None);
+    // Without the formatter's trailing newline, which would otherwise end up in the input
+    let recalled = spans_text(&spans).trim_end().to_string();
+
+    if let Some(draft) = editing() {
+        return rsx! {
+            div { class: "user-input editing",
+                textarea { class: "text-box", rows: 2, value: "{draft}",
+                           oninput: move |e| editing.set(Some(e.value())) }
+                div { class: "entry-actions",
+                    button { onclick: move |_| {
+                                 if let Some(code) = editing() {
+                                     onaction.call(EntryAction::Edit(code));
+                                 }
+                                 editing.set(None);
+                             },
+                             "Run" }
+                    button { onclick: move |_| editing.set(None), "Cancel" }
+                }
+            }
+        };
+    }
+
+    rsx! {
+        div { class: "user-input-entry",
+            p { class: "user-input",
+                onclick: move |_| {
+                    if input_contents().is_empty() {
+                        input_contents.set(recalled.clone());
+                    }
+                },
+                CodeSpans { spans, rainbow }
+            }
+            button { class: "entry-menu-button",
+                     onclick: move |_| {
+                         let open = menu_open();
+                         menu_open.set(!open);
+                     },
+                     "⋯" }
+            if menu_open() {
+                div { class: "entry-actions",
+                    button { onclick: {
+                                 let code = code.clone();
+                                 move |_| copy_to_clipboard(&code)
+                             },
+                             "Copy" }
+                    button { onclick: {
+                                 let code = code.clone();
+                                 move |_| copy_to_clipboard(&share::share_url(&code))
+                             },
+                             "Share" }
+                    button { onclick: move |_| onaction.call(EntryAction::Rerun), "Re-run" }
+                    button { onclick: {
+                                 let code = code.clone();
+                                 move |_| {
+                                     editing.set(Some(code.clone()));
+                                     menu_open.set(false);
+                                 }
+                             },
+                             "Edit" }
+                    button { onclick: move |_| onaction.call(EntryAction::Delete), "Delete" }
+                }
+            }
+        }
+    }
+}
+
+/// A run in the scrollback: its input followed by its outputs (or its error)
+#[component]
+pub fn RunEntry(
+    run: RunRecord,
+    buffer_contents: Signal<Vec<RunRecord>>,
+    input_contents: Signal<String>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+    mut app_error: Signal<Option<String>>,
+) -> Element {
+    let id = run.id;
+    let mut outputs: Vec<(usize, ScrollbackOutput)> = run.outputs.into_iter().enumerate().collect();
+    if settings.read().stack_ordering == StackOrdering::BottomAtTop {
+        outputs.reverse();
+    }
+    rsx! {
+        InputEntry {
+            code: run.code.clone(),
+            spans: run.spans,
+            rainbow: settings.read().rainbow_brackets,
+            input_contents,
+            onaction: move |action| match action {
+                EntryAction::Rerun => {
+                    let code = run.code.clone();
+                    rerun_entry(buffer_contents, id, &code, buffers, &settings.read());
+                }
+                EntryAction::Edit(code) => rerun_entry(buffer_contents, id, &code, buffers, &settings.read()),
+                EntryAction::Delete => delete_entry(buffer_contents, id),
+            }
+        }
+        for (j, output) in outputs {
+            ScrollbackOutputView {
+                output,
+                pretty: settings.read().pretty_arrays,
+                max_chars: settings.read().max_output_chars,
+                ordering: settings.read().stack_ordering.clone(),
+                onrekind: move |kind: OutputKind| {
+                    let mut runs = buffer_contents.write();
+                    let Some(run) = runs.iter_mut().find(|r| r.id == id) else {
+                        return;
+                    };
+                    if let Err(e) = run.outputs[j].set_kind(kind, &settings.read()) {
+                        app_error.set(Some(format!("Cannot show output as {}: {e}", kind.name())));
+                    }
+                }
+            }
+        }
+        if let Some(e) = run.error {
+            p { class: "user-result user-error", "{e}" }
+        }
+        p { class: "run-timing",
+            "compiled in {format_duration(run.timings.compile)} · ran in {format_duration(run.timings.execution)}"
+            if let Some(bench) = run.benchmark {
+                br {}
+                "×{bench.runs}: min {format_duration(bench.min)} · median {format_duration(bench.median)} · max {format_duration(bench.max)}"
+            }
+        }
+    }
+}
+
+/// Greets first-time users with a few programs to try
+#[component]
+pub fn Welcome(
+    buffer_contents: Signal<Vec<RunRecord>>,
+    history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+) -> Element {
+    rsx! {
+        div { class: "welcome",
+            h2 { "Welcome to uiuapp" }
+            p { "Type with the keypad below (hold a key for its siblings) and press Run. Or tap one of these to see what Uiua can do:" }
+            for (description, code) in ONBOARDING_EXAMPLES {
+                div { class: "welcome-example",
+                      onclick: move |_| {
+                          run_into_scrollback(code, buffer_contents, history, buffers, settings);
+                      },
+                      span { class: "welcome-description", "{description}" }
+                      HighlightedCode { code, format: settings.read().format, rainbow: settings.read().rainbow_brackets }
+                }
+            }
+        }
+    }
+}
+
+/// Highlighted code, where tapping a bracket shows the one matching it,
+/// and tapping a glyph or name shows what it is
+#[component]
+pub fn CodeSpans(spans: Vec<UiuappHistorySpan>, rainbow: bool) -> Element {
+    let mut selected_pair: Signal<Option<usize>> = use_signal(|| None);
+    let mut shown_info: Signal<Option<usize>> = use_signal(|| None);
+    let popover = shown_info().and_then(|i| match &spans.get(i) {
+        Some(UiuappHistorySpan::StyledCode { info, .. }) => info.clone(),
+        _ => None,
+    });
+    rsx! {
+        for (i, uhs) in spans.into_iter().enumerate() {
+            match uhs {
+                UiuappHistorySpan::UnstyledCode { text } => rsx! { span { "{text}" } },
+                UiuappHistorySpan::StyledCode { class: c, text, bracket: None, info: None } => rsx! { span { class: "{c}", "{text}"} },
+                UiuappHistorySpan::StyledCode { class: c, text, bracket: None, info: Some(_) } => rsx! {
+                    span { class: "{c}",
+                           onclick: move |e| {
+                               e.stop_propagation();
+                               let shown = (shown_info() != Some(i)).then_some(i);
+                               shown_info.set(shown);
+                           },
+                           "{text}" }
+                },
+                UiuappHistorySpan::StyledCode { class: c, text, bracket: Some(b), .. } => {
+                    let nesting = if rainbow { format!(" bracket-depth-{}", b.depth % 6) } else { String::new() };
+                    let matching = if !b.matched {
+                        " unmatched-bracket"
+                    } else if selected_pair() == Some(b.pair) {
+                        " bracket-match"
+                    } else {
+                        ""
+                    };
+                    rsx! {
+                        span { class: "{c}{nesting}{matching}",
+                               onclick: move |e| {
+                                   e.stop_propagation();
+                                   let pair = (selected_pair() != Some(b.pair)).then_some(b.pair);
+                                   selected_pair.set(pair);
+                               },
+                               "{text}" }
+                    }
+                }
+                UiuappHistorySpan::Whitspace(text) => rsx! { span { "{text}" } },
+            }
+        }
+        if let Some(info) = popover {
+            span { class: "span-info",
+                   onclick: move |e| {
+                       e.stop_propagation();
+                       shown_info.set(None);
+                   },
+                   strong { "{info.name}" }
+                   if let Some(sig) = info.signature {
+                       span { class: "primitive-signature", " {sig}" }
+                   }
+                   if let Some(docs) = info.docs {
+                       span { class: "span-info-docs", "{docs}" }
+                   }
+            }
+        }
+    }
+}
+
+/// Read-only, highlighted code
+#[component]
+pub fn HighlightedCode(code: String, format: FormatSettings, rainbow: bool) -> Element {
+    let spans = highlight_code(&code, &format);
+    rsx! {
+        code { class: "highlighted-code",
+            CodeSpans { spans, rainbow }
+        }
+    }
+}
+
+/// The overlays that can be opened from the top bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    Settings,
+    History,
+    Examples,
+    Reference,
+    Files,
+}
+
+/// Opens `p`, or closes it if it was already open
+pub fn toggle_panel(mut panel: Signal<Option<Panel>>, p: Panel) {
+    let open = panel() == Some(p);
+    panel.set(if open { None } else { Some(p) });
+}
+
+/// Export of the scrollback as a `.ua` file, and import of one
+#[component]
+pub fn FilesPanel(
+    input_contents: Signal<String>,
+    mut buffer_contents: Signal<Vec<RunRecord>>,
+    mut history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+    panel: Signal<Option<Panel>>,
+) -> Element {
+    let mut run_lines = use_signal(|| false);
+    let file_name = format!("{}.ua", buffers.read().active().name);
+    rsx! {
+        div { class: "files-panel",
+            a { class: "export-link",
+                href: session::export_url(&buffer_contents.read()),
+                download: "{file_name}",
+                "Export session as {file_name}" }
+            label { class: "setting",
+                input { r#type: "checkbox", checked: run_lines(),
+                        onchange: move |e| run_lines.set(e.checked()) }
+                "Run imported files line by line"
+            }
+            // Read through the FileReader api, which is what the file engine is backed by on web
+            input { r#type: "file", accept: ".ua,text/plain",
+                    onchange: move |e| async move {
+                        let Some(engine) = e.files() else { return };
+                        for name in engine.files() {
+                            let Some(file) = engine.read_file_to_string(&name).await else {
+                                warn!("Could not read '{name}'");
+                                continue;
+                            };
+                            if run_lines() {
+                                let prelude = buffers.read().prelude();
+                                for run in session::run_lines(&file, &prelude, &settings.read()) {
+                                    history.write().push(&run.code);
+                                    buffer_contents.write().push(run);
+                                }
+                                history.read().save();
+                            } else {
+                                input_contents.set(file);
+                            }
+                        }
+                        panel.set(None);
+                    } }
+        }
+    }
+}
+
+/// A tab per scratch buffer, plus the options of the active one
+#[component]
+pub fn BufferTabs(
+    buffers: Signal<ScratchBuffers>,
+    input_contents: Signal<String>,
+    buffer_contents: Signal<Vec<RunRecord>>,
+    settings: Signal<Settings>,
+) -> Element {
+    let mut options_open = use_signal(|| false);
+    let active = buffers.read().active().clone();
+    rsx! {
+        div { class: "buffer-tabs",
+            for buffer in buffers.read().buffers.clone() {
+                button { class: if buffer.id == active.id { "buffer-tab active-tab" } else { "buffer-tab" },
+                         onclick: move |_| {
+                             if buffer.id == active.id {
+                                 let o = options_open();
+                                 options_open.set(!o);
+                             } else {
+                                 switch_buffer(buffers, input_contents, buffer_contents, buffer.id, &settings.read());
+                                 options_open.set(false);
+                             }
+                         },
+                         if buffer.is_library { "📚 " }
+                         "{buffer.name}"
+                }
+            }
+            button { class: "buffer-tab",
+                     onclick: move |_| {
+                         let id = buffers.write().add();
+                         switch_buffer(buffers, input_contents, buffer_contents, id, &settings.read());
+                     },
+                     "+" }
+        }
+        if options_open() {
+            div { class: "buffer-options",
+                input { value: "{active.name}",
+                        oninput: move |e| {
+                            buffers.write().active_mut().name = e.value();
+                            buffers.read().save();
+                        } }
+                label { class: "setting",
+                    input { r#type: "checkbox", checked: active.is_library,
+                            onchange: move |e| {
+                                buffers.write().active_mut().is_library = e.checked();
+                                buffers.read().save();
+                            } }
+                    "Library (loaded before code in other buffers)"
+                }
+                label { class: "setting",
+                    input { r#type: "checkbox", checked: active.isolated,
+                            onchange: move |e| {
+                                buffers.write().active_mut().isolated = e.checked();
+                                buffers.read().save();
+                            } }
+                    "Isolated (ignores libraries)"
+                }
+                button { disabled: buffers.read().buffers.len() <= 1,
+                         onclick: move |_| {
+                             buffers.write().remove(active.id);
+                             let id = buffers.peek().active;
+                             buffers.peek().save();
+                             input_contents.set(buffers.peek().active().input.clone());
+                             buffer_contents.set(load_runs(id, &settings.read()));
+                             options_open.set(false);
+                         },
+                         "Delete buffer" }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn HistoryPanel(
+    input_contents: Signal<String>,
+    history: Signal<InputHistory>,
+    panel: Signal<Option<Panel>>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    rsx! {
+        div { class: "history-panel",
+            input { class: "history-search", placeholder: "Search history",
+                    value: query,
+                    oninput: move |e| query.set(e.value()) }
+            for entry in history.read().search(&query()) {
+                pre { class: "history-entry",
+                      onclick: move |_| {
+                          input_contents.set(entry.clone());
+                          panel.set(None);
+                      },
+                      "{entry}"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ExamplesPanel(
+    input_contents: Signal<String>,
+    buffer_contents: Signal<Vec<RunRecord>>,
+    history: Signal<InputHistory>,
+    buffers: Signal<ScratchBuffers>,
+    settings: Signal<Settings>,
+    panel: Signal<Option<Panel>>,
+) -> Element {
+    rsx! {
+        div { class: "examples-panel",
+            for (topic, group) in examples_by_topic() {
+                details { class: "example-topic",
+                    summary { "{topic}" }
+                    for example in group {
+                        div { class: "example",
+                            h4 { "{example.title}" }
+                            p { class: "example-description", "{example.description}" }
+                            HighlightedCode { code: example.code.clone(), format: settings.read().format, rainbow: settings.read().rainbow_brackets }
+                            div { class: "entry-actions",
+                                button { onclick: move |_| {
+                                             input_contents.set(example.code.clone());
+                                             panel.set(None);
+                                         },
+                                         "Load" }
+                                button { onclick: move |_| {
+                                             run_into_scrollback(&example.code, buffer_contents, history, buffers, settings);
+                                             panel.set(None);
+                                         },
+                                         "Run" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn SettingsPanel(settings: Signal<Settings>) -> Element {
+    let s = settings();
+    rsx! {
+        div { class: "settings-panel",
+            label { class: "setting",
+                "Theme"
+                select {
+                    onchange: move |e| {
+                        if let Some(theme) = Theme::ALL.into_iter().find(|t| t.attr() == e.value()) {
+                            settings.write().theme = theme;
+                        }
+                    },
+                    for theme in Theme::ALL {
+                        option { value: theme.attr(), selected: s.theme == theme, "{theme.name()}" }
+                    }
+                }
+            }
+            label { class: "setting",
+                "Pride glyphs"
+                select {
+                    onchange: move |e| {
+                        if let Some(flag) = PrideFlag::ALL.into_iter().find(|f| f.name() == e.value()) {
+                            settings.write().gayness = flag;
+                        }
+                    },
+                    for flag in PrideFlag::ALL {
+                        option { value: flag.name(), selected: s.gayness == flag, "{flag.name()}" }
+                    }
+                }
+            }
+            label { class: "setting",
+                "Clean input on run"
+                input { r#type: "checkbox", checked: s.clean_input_on_run,
+                        onchange: move |e| settings.write().clean_input_on_run = e.checked() }
+            }
+            label { class: "setting",
+                "Pretty arrays"
+                input { r#type: "checkbox", checked: s.pretty_arrays,
+                        onchange: move |e| settings.write().pretty_arrays = e.checked() }
+            }
+            label { class: "setting",
+                "Collapse text outputs after (chars)"
+                input { r#type: "number", min: 1, value: "{s.max_output_chars}",
+                        onchange: move |e| if let Ok(v) = e.value().parse() { settings.write().max_output_chars = v } }
+            }
+            label { class: "setting",
+                "Benchmark runs (0 or 1 to disable)"
+                input { r#type: "number", min: 0, value: "{s.benchmark_runs}",
+                        onchange: move |e| if let Ok(v) = e.value().parse() { settings.write().benchmark_runs = v } }
+            }
+            label { class: "setting",
+                "Stack ordering"
+                select {
+                    onchange: move |e| {
+                        settings.write().stack_ordering = match e.value().as_str() {
+                            "top" => StackOrdering::TopAtTop,
+                            _ => StackOrdering::BottomAtTop,
+                        };
+                    },
+                    option { value: "bottom", selected: s.stack_ordering == StackOrdering::BottomAtTop, "Bottom at top" }
+                    option { value: "top", selected: s.stack_ordering == StackOrdering::TopAtTop, "Top at top" }
+                }
+            }
+            h3 { "Formatting" }
+            label { class: "setting",
+                "Format input on run"
+                input { r#type: "checkbox", checked: s.format_on_run,
+                        onchange: move |e| settings.write().format_on_run = e.checked() }
+            }
+            label { class: "setting",
+                "Rainbow brackets"
+                input { r#type: "checkbox", checked: s.rainbow_brackets,
+                        onchange: move |e| settings.write().rainbow_brackets = e.checked() }
+            }
+            label { class: "setting",
+                "Turn names into glyphs while typing"
+                input { r#type: "checkbox", checked: s.live_glyphs,
+                        onchange: move |e| settings.write().live_glyphs = e.checked() }
+            }
+            label { class: "setting",
+                "Indentation of multiline arrays and functions"
+                input { r#type: "number", min: 0, value: "{s.format.multiline_indent}",
+                        onchange: move |e| if let Ok(v) = e.value().parse() { settings.write().format.multiline_indent = v } }
+            }
+            label { class: "setting",
+                "Trailing newline"
+                input { r#type: "checkbox", checked: s.format.trailing_newline,
+                        onchange: move |e| settings.write().format.trailing_newline = e.checked() }
+            }
+            label { class: "setting",
+                "Space after # in comments"
+                input { r#type: "checkbox", checked: s.format.comment_space_after_hash,
+                        onchange: move |e| settings.write().format.comment_space_after_hash = e.checked() }
+            }
+            label { class: "setting",
+                "Align end-of-line comments"
+                input { r#type: "checkbox", checked: s.format.align_comments,
+                        onchange: move |e| settings.write().format.align_comments = e.checked() }
+            }
+            label { class: "setting",
+                "Indent item imports"
+                input { r#type: "checkbox", checked: s.format.indent_item_imports,
+                        onchange: move |e| settings.write().format.indent_item_imports = e.checked() }
+            }
+            h3 { "Media" }
+            label { class: "setting",
+                "Minimum image size"
+                input { r#type: "number", min: 1, value: "{s.media.min_image_dim}",
+                        onchange: move |e| if let Ok(v) = e.value().parse() { settings.write().media.min_image_dim = v } }
+            }
+            label { class: "setting",
+                "GIF frames per second"
+                input { r#type: "number", min: 1, step: "any", value: "{s.media.gif_fps}",
+                        onchange: move |e| if let Ok(v) = e.value().parse::<f64>() { if v > 0. { settings.write().media.gif_fps = v } } }
+            }
+            label { class: "setting",
+                "GIF minimum frames"
+                input { r#type: "number", min: 1, value: "{s.media.gif_min_frames}",
+                        onchange: move |e| if let Ok(v) = e.value().parse() { settings.write().media.gif_min_frames = v } }
+            }
+            label { class: "setting",
+                "Detect audio"
+                input { r#type: "checkbox", checked: s.media.detect_audio,
+                        onchange: move |e| settings.write().media.detect_audio = e.checked() }
+            }
+            label { class: "setting",
+                "Audio sample rate"
+                input { r#type: "number", min: 1, value: "{s.audio_sample_time}",
+                        onchange: move |e| if let Ok(v) = e.value().parse::<u32>() { if v > 0 { settings.write().audio_sample_time = v } } }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct RadialInfo {
     pub is_active: bool,
@@ -161,7 +832,6 @@ impl RadialInfo {
         let mut gray = true;
         while count < 100. {
             let color = if gray { "gray" } else { "white" };
-            let radius = 60.;
             let upper = count + incr;
             initial.push_str(format!("{} {count}% {upper}%,", color).as_str());
             count = upper;